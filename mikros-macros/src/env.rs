@@ -4,44 +4,210 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::DeriveInput;
 
-pub fn generate(input: DeriveInput) -> TokenStream {
+pub fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
     let struct_name = input.ident.clone();
-    let (field_initializers, default_checks, attributes) = match parser::parse_fields(input) {
-        Ok(fields) => fields,
-        Err(err) => panic!("{}", err),
-    };
+    let (fields, attributes) = parser::parse_fields(input)?;
+
+    let field_initializers = &fields.initializers;
+    let default_checks = &fields.default_checks;
+    let variables = &fields.variables;
 
     let delimiter = &attributes.delimiter;
+    let prefix = &attributes.prefix;
     let expanded = quote! {
         impl #struct_name {
             pub fn from_env() -> Self {
-                let suffix: Option<String> = None;
-                let delimiter = #delimiter;
-                let use_defaults = true;
+                Self::from_source(None, true, &|name| std::env::var(name).ok())
+            }
 
-                Self {
-                    #(#field_initializers),*
+            pub fn from_env_with_suffix(suffix: &str, use_defaults: bool) -> Self {
+                Self::from_source(Some(suffix), use_defaults, &|name| std::env::var(name).ok())
+            }
+
+            /// Loads the structure using `source` to retrieve each variable
+            /// value instead of reading them from the process environment.
+            ///
+            /// It panics if a variable has an invalid value or if a required
+            /// one is not set. Optional (Option<T>) fields with invalid values
+            /// are left unset instead.
+            pub fn from_source(suffix: Option<&str>, use_defaults: bool, source: &dyn Fn(&str) -> Option<String>) -> Self {
+                let mut errors = Vec::new();
+                let value = Self::load_env_fields("", suffix, use_defaults, false, false, source, &mut errors);
+
+                if !errors.is_empty() {
+                    panic!("failed to load environment variables: {}", errors.join("; "));
                 }
+
+                value
             }
 
-            pub fn from_env_with_suffix(suffix: &str, use_defaults: bool) -> Self {
-                let suffix = Some(suffix.to_string());
+            /// Loads the structure from the process environment, returning
+            /// every invalid or missing variable instead of panicking.
+            pub fn try_from_env() -> Result<Self, Vec<String>> {
+                Self::try_from_source(None, true, &|name| std::env::var(name).ok())
+            }
+
+            /// Loads the structure using `source`, returning every invalid or
+            /// missing variable instead of panicking.
+            pub fn try_from_source(suffix: Option<&str>, use_defaults: bool, source: &dyn Fn(&str) -> Option<String>) -> Result<Self, Vec<String>> {
+                let mut errors = Vec::new();
+                let value = Self::load_env_fields("", suffix, use_defaults, false, true, source, &mut errors);
+
+                if errors.is_empty() {
+                    Ok(value)
+                } else {
+                    Err(errors)
+                }
+            }
+
+            /// Loads the structure from the process environment, reading each
+            /// variable with `suffix` first and falling back to its name without
+            /// it and then to its default value. Required variables are only
+            /// missing when neither name is set.
+            pub fn try_from_env_with_suffix(suffix: &str) -> Result<Self, Vec<String>> {
+                Self::try_from_source_with_suffix(suffix, &|name| std::env::var(name).ok())
+            }
+
+            /// Same as `try_from_env_with_suffix`, but using `source` to retrieve
+            /// each variable value.
+            pub fn try_from_source_with_suffix(suffix: &str, source: &dyn Fn(&str) -> Option<String>) -> Result<Self, Vec<String>> {
+                let mut errors = Vec::new();
+                let value = Self::load_env_fields("", Some(suffix), true, true, true, source, &mut errors);
+
+                if errors.is_empty() {
+                    Ok(value)
+                } else {
+                    Err(errors)
+                }
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_variables, clippy::ptr_arg)]
+            pub fn load_env_fields(
+                prefix: &str,
+                suffix: Option<&str>,
+                use_defaults: bool,
+                fallback: bool,
+                strict: bool,
+                source: &dyn Fn(&str) -> Option<String>,
+                errors: &mut Vec<String>,
+            ) -> Self {
+                let suffix = suffix.map(|s| s.to_string());
                 let delimiter = #delimiter;
+                let prefix = format!("{}{}", prefix, #prefix);
 
                 Self {
                     #(#field_initializers),*
                 }
             }
 
-            fn load_env(name: &str, suffix: &Option<String>, delimiter: &str) -> Result<String, std::env::VarError> {
-                let key = match suffix {
+            /// Returns information about every variable read by the structure,
+            /// including the names used when `suffix` is given.
+            pub fn env_variables(suffix: Option<&str>) -> Vec<mikros::env::Variable> {
+                let mut variables = Vec::new();
+                Self::collect_env_variables("", suffix, &mut variables);
+                variables
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_variables, clippy::ptr_arg)]
+            pub fn collect_env_variables(prefix: &str, suffix: Option<&str>, variables: &mut Vec<mikros::env::Variable>) {
+                let prefix = format!("{}{}", prefix, #prefix);
+                #(#variables)*
+            }
+
+            fn env_key(name: &str, suffix: &Option<String>, delimiter: &str) -> String {
+                match suffix {
                     None => name.to_string(),
                     Some(s) => format!("{}{}{}", name, delimiter, s)
+                }
+            }
+
+            fn load_env(name: &str, suffix: &Option<String>, delimiter: &str, source: &dyn Fn(&str) -> Option<String>) -> Result<String, std::env::VarError> {
+                source(&Self::env_key(name, suffix, delimiter)).ok_or(std::env::VarError::NotPresent)
+            }
+
+            // Retrieves a variable value, returning it with the name used to
+            // find it. With `fallback`, an unset or empty suffixed variable
+            // falls back to the one without the suffix.
+            #[allow(dead_code)]
+            fn env_lookup(
+                name: &str,
+                suffix: &Option<String>,
+                delimiter: &str,
+                fallback: bool,
+                source: &dyn Fn(&str) -> Option<String>,
+            ) -> (String, Option<String>) {
+                let key = Self::env_key(name, suffix, delimiter);
+                match source(&key) {
+                    Some(v) if fallback && suffix.is_some() && v.is_empty() => (name.to_string(), source(name)),
+                    None if fallback && suffix.is_some() => (name.to_string(), source(name)),
+                    value => (key, value),
+                }
+            }
+
+            #[allow(dead_code)]
+            fn env_parse_value<T>(key: &str, value: &str, errors: &mut Vec<String>) -> Option<T>
+            where
+                T: std::str::FromStr,
+                T::Err: std::fmt::Display,
+            {
+                match value.parse() {
+                    Ok(v) => Some(v),
+                    Err(e) => {
+                        errors.push(format!("{}: invalid value: {}", key, e));
+                        None
+                    }
+                }
+            }
+
+            // Durations are written as a sequence of numbers followed by
+            // their units (ms, s, m, h or d), like '1h30m'. A single number
+            // without unit is handled as seconds.
+            #[allow(dead_code)]
+            fn env_parse_duration(key: &str, value: &str, errors: &mut Vec<String>) -> Option<std::time::Duration> {
+                let parse = |value: &str| -> Option<std::time::Duration> {
+                    let mut rest = value.trim();
+                    if rest.is_empty() {
+                        return None;
+                    }
+
+                    if let Ok(seconds) = rest.parse::<f64>() {
+                        return std::time::Duration::try_from_secs_f64(seconds).ok();
+                    }
+
+                    let mut total = 0.0;
+                    while !rest.is_empty() {
+                        let number_len = rest.find(|c: char| !c.is_ascii_digit() && c != '.')?;
+                        let (number, tail) = rest.split_at(number_len);
+                        let unit_len = tail.find(|c: char| c.is_ascii_digit()).unwrap_or(tail.len());
+                        let (unit, tail) = tail.split_at(unit_len);
+                        let number: f64 = number.parse().ok()?;
+
+                        total += number * match unit {
+                            "ms" => 0.001,
+                            "s" => 1.0,
+                            "m" => 60.0,
+                            "h" => 3600.0,
+                            "d" => 86400.0,
+                            _ => return None,
+                        };
+
+                        rest = tail;
+                    }
+
+                    std::time::Duration::try_from_secs_f64(total).ok()
                 };
 
-                std::env::var(key)
+                let duration = parse(value);
+                if duration.is_none() {
+                    errors.push(format!("{}: invalid duration '{}'", key, value));
+                }
+
+                duration
             }
 
+            #[allow(clippy::cmp_owned)]
             pub fn check_defaults(&self) -> Vec<(&'static str, bool)> {
                 vec![
                     #(#default_checks),*
@@ -50,5 +216,5 @@ pub fn generate(input: DeriveInput) -> TokenStream {
         }
     };
 
-    expanded
+    Ok(expanded)
 }
@@ -2,107 +2,269 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{Attribute, DeriveInput, Field, Ident, Lit};
 
+// The default separator used to split list (Vec<T>) values.
+const DEFAULT_SEPARATOR: &str = ",";
+
 #[derive(Default)]
 struct FieldAttributes {
     env_name: Option<String>,
     default_value: Option<String>,
+    separator: Option<String>,
+    required: bool,
+    flatten: bool,
+    prefix: Option<String>,
 }
 
-impl FieldAttributes {
-    fn into_token_stream(
-        self,
-        field_name: &Ident,
-        struct_name: &Ident,
-        field_type: &syn::Type,
-    ) -> proc_macro::TokenStream {
-        // Check if we're dealing with an Option<T> member
-        let is_option = matches!(
-            &field_type,
-            syn::Type::Path(type_path) if type_path.path.segments.len() == 1
-                && type_path.path.segments[0].ident == "Option"
-        );
-
-        let expanded = if let Some(env_name) = self.env_name {
-            let default_value = self.default_value.unwrap_or_default();
-
-            if is_option {
+// How a field value must be decoded from its string representation.
+enum ValueKind {
+    // Any type implementing FromStr.
+    Scalar(syn::Type),
+
+    // A std::time::Duration, written like '30s', '500ms' or '1h30m'.
+    Duration,
+
+    // A Vec<T> whose items are separated by a separator.
+    List(Box<ValueKind>, String),
+}
+
+impl ValueKind {
+    // Builds the expression that decodes `value` (a &str expression) into an
+    // Option<T>, registering parse errors into `errors`.
+    fn decode(&self, value: TokenStream) -> TokenStream {
+        match self {
+            ValueKind::Scalar(ty) => quote! {
+                Self::env_parse_value::<#ty>(&key, #value, errors)
+            },
+            ValueKind::Duration => quote! {
+                Self::env_parse_duration(&key, #value, errors)
+            },
+            ValueKind::List(item, separator) => {
+                let item_decode = item.decode(quote! { item });
                 quote! {
-                    #field_name: {
-                        let env_value = #struct_name::load_env(#env_name, &suffix, delimiter);
-                        match env_value {
-                            Ok(v) if v.is_empty() || v == "None" => None,
-                            Ok(v) => v.parse().ok(),
-                            Err(_) if use_defaults => if #default_value == "None" {
-                                None
-                            } else {
-                                #default_value.parse().ok()
-                            },
-                            Err(_) => None,
+                    {
+                        let mut items = Vec::new();
+                        let mut valid = true;
+
+                        for item in #value.split(#separator).map(str::trim).filter(|i| !i.is_empty()) {
+                            match #item_decode {
+                                Some(i) => items.push(i),
+                                None => valid = false,
+                            }
                         }
+
+                        if valid { Some(items) } else { None }
                     }
                 }
-            } else {
-                quote! {
-                    #field_name: {
-                        let env_value = #struct_name::load_env(#env_name, &suffix, delimiter);
-                        match env_value {
-                            Ok(v) => v.parse().expect("failed to parse environment variable"),
-                            Err(_) if use_defaults => #default_value.parse().expect("failed to parse variable default value"),
-                            Err(_) => Default::default(),
+            }
+        }
+    }
+
+    fn is_scalar(&self) -> bool {
+        matches!(self, ValueKind::Scalar(_))
+    }
+}
+
+// The field type with its optional (Option<T>) wrapper already removed.
+struct FieldType {
+    optional: bool,
+    kind: ValueKind,
+}
+
+impl FieldType {
+    fn new(ty: &syn::Type, separator: Option<String>) -> syn::Result<Self> {
+        let (optional, inner) = match generic_argument(ty, "Option") {
+            Some(inner) => (true, inner),
+            None => (false, ty),
+        };
+
+        let kind = match generic_argument(inner, "Vec") {
+            Some(item) => ValueKind::List(
+                Box::new(value_kind(item)),
+                separator.unwrap_or_else(|| DEFAULT_SEPARATOR.to_string()),
+            ),
+            None if separator.is_some() => {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "'separator' attribute can only be used with Vec fields",
+                ));
+            }
+            None => value_kind(inner),
+        };
+
+        Ok(Self { optional, kind })
+    }
+}
+
+fn last_segment(ty: &syn::Type) -> Option<&syn::PathSegment> {
+    match ty {
+        syn::Type::Path(type_path) => type_path.path.segments.last(),
+        _ => None,
+    }
+}
+
+// Returns the generic argument of `ty` if it is a `name<T>` type.
+fn generic_argument<'a>(ty: &'a syn::Type, name: &str) -> Option<&'a syn::Type> {
+    let segment = last_segment(ty)?;
+    if segment.ident != name {
+        return None;
+    }
+
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+fn value_kind(ty: &syn::Type) -> ValueKind {
+    match last_segment(ty) {
+        Some(segment) if segment.ident == "Duration" => ValueKind::Duration,
+        _ => ValueKind::Scalar(ty.clone()),
+    }
+}
+
+impl FieldAttributes {
+    fn into_token_stream(self, field_name: &Ident, field_type: &syn::Type) -> syn::Result<TokenStream> {
+        if self.flatten {
+            let prefix = self.prefix.unwrap_or_default();
+            return Ok(quote! {
+                #field_name: <#field_type>::load_env_fields(
+                    &format!("{}{}", prefix, #prefix),
+                    suffix.as_deref(),
+                    use_defaults,
+                    fallback,
+                    strict,
+                    source,
+                    errors,
+                )
+            });
+        }
+
+        let Some(env_name) = self.env_name else {
+            // Members without attribute will be initialized with their
+            // default values (do not mix with 'default' attribute).
+            return Ok(quote! {
+                #field_name: Default::default()
+            });
+        };
+
+        let field_type = FieldType::new(field_type, self.separator)?;
+
+        let env_decode = field_type.kind.decode(quote! { v.as_str() });
+        let missing = if self.required {
+            quote! {
+                errors.push(format!("{}: required variable is not set", key));
+            }
+        } else {
+            quote! {}
+        };
+
+        let expanded = if field_type.optional {
+            let default_value = match &self.default_value {
+                Some(default) if default != "None" => field_type.kind.decode(quote! { #default }),
+                _ => quote! { { #missing None } },
+            };
+
+            quote! {
+                #field_name: {
+                    let name = format!("{}{}", prefix, #env_name);
+                    let (key, value) = Self::env_lookup(&name, &suffix, delimiter, fallback, source);
+                    match value {
+                        Some(v) if v.is_empty() || v == "None" => None,
+                        Some(v) => {
+                            // Invalid values are only reported by the try_*
+                            // loaders, the others leave the field unset.
+                            let mut value_errors = Vec::new();
+                            let value = {
+                                let errors = &mut value_errors;
+                                #env_decode
+                            };
+
+                            if strict {
+                                errors.extend(value_errors);
+                            }
+
+                            value
                         }
+                        None if use_defaults => #default_value,
+                        None => None,
                     }
                 }
             }
         } else {
-            // Members without attribute will be initialized with their
-            // default values (do not mix with 'default' attribute).
+            let default_value = match &self.default_value {
+                Some(default) => {
+                    let decode = field_type.kind.decode(quote! { #default });
+                    quote! { #decode.unwrap_or_default() }
+                }
+                None => quote! { { #missing Default::default() } },
+            };
+
             quote! {
-                #field_name: Default::default()
+                #field_name: {
+                    let name = format!("{}{}", prefix, #env_name);
+                    let (key, value) = Self::env_lookup(&name, &suffix, delimiter, fallback, source);
+                    match value {
+                        Some(v) => #env_decode.unwrap_or_default(),
+                        None if use_defaults => #default_value,
+                        None => Default::default(),
+                    }
+                }
             }
         };
 
-        proc_macro::TokenStream::from(expanded)
+        Ok(expanded)
     }
 }
 
+// The code generated for every struct field.
+#[derive(Default)]
+pub(crate) struct Fields {
+    pub(crate) initializers: Vec<TokenStream>,
+    pub(crate) default_checks: Vec<TokenStream>,
+    pub(crate) variables: Vec<TokenStream>,
+}
+
 pub(crate) struct StructAttributes {
     pub(crate) delimiter: String,
+    pub(crate) prefix: String,
 }
 
 impl Default for StructAttributes {
     fn default() -> Self {
         Self {
             delimiter: "_".to_string(),
+            prefix: String::new(),
         }
     }
 }
 
-pub(crate) fn parse_fields(input: DeriveInput) -> Result<(Vec<TokenStream>, Vec<TokenStream>, StructAttributes), String> {
-    let struct_name = &input.ident;
-
+pub(crate) fn parse_fields(input: DeriveInput) -> syn::Result<(Fields, StructAttributes)> {
     // Parse struct level attributes
     let attributes = parse_struct_attributes(&input)?;
 
     // Parse fields
     let fields = match input.data {
         syn::Data::Struct(data) => data.fields,
-        _ => return Err("Env can only be derived for structs".to_string()),
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input.ident,
+                "Env can only be derived for structs",
+            ));
+        }
     };
 
-    let mut field_initializers: Vec<TokenStream> = Vec::new();
-    let mut default_checks: Vec<TokenStream> = Vec::new();
-
+    let mut parsed_fields = Fields::default();
     for field in &fields {
-        let (initializer, check) = parse_field(field, struct_name)?;
-
-        field_initializers.push(initializer);
-        default_checks.push(check);
+        parse_field(field, &attributes, &mut parsed_fields)?;
     }
 
-    Ok((field_initializers, default_checks, attributes))
+    Ok((parsed_fields, attributes))
 }
 
-fn parse_struct_attributes(input: &DeriveInput) -> Result<StructAttributes, String> {
+fn parse_struct_attributes(input: &DeriveInput) -> syn::Result<StructAttributes> {
     let mut attributes = StructAttributes::default();
 
     for attr in &input.attrs {
@@ -112,6 +274,10 @@ fn parse_struct_attributes(input: &DeriveInput) -> Result<StructAttributes, Stri
                     if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
                         attributes.delimiter = v.value();
                     }
+                } else if meta.path.is_ident("prefix") {
+                    if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
+                        attributes.prefix = v.value();
+                    }
                 }
 
                 Ok(())
@@ -122,9 +288,9 @@ fn parse_struct_attributes(input: &DeriveInput) -> Result<StructAttributes, Stri
     Ok(attributes)
 }
 
-fn parse_field(field: &Field, struct_name: &Ident) -> Result<(TokenStream, TokenStream), String> {
+fn parse_field(field: &Field, struct_attributes: &StructAttributes, fields: &mut Fields) -> syn::Result<()> {
     let Some(field_name) = field.ident.as_ref() else {
-        return Err("expected a field name".to_string());
+        return Err(syn::Error::new_spanned(field, "expected a field name"));
     };
 
     let mut attributes = FieldAttributes::default();
@@ -134,74 +300,227 @@ fn parse_field(field: &Field, struct_name: &Ident) -> Result<(TokenStream, Token
         }
     }
 
-    let default_check = generate_default_check(field_name, &attributes.default_value, &field.ty);
-    let initializer = attributes
-        .into_token_stream(field_name, struct_name, &field.ty)
-        .into();
+    // Nested structures do not have a default value of their own.
+    if !attributes.flatten {
+        fields
+            .default_checks
+            .push(generate_default_check(field_name, &attributes, &field.ty)?);
+    }
+
+    if let Some(variable) = generate_variable(field, &attributes, struct_attributes)? {
+        fields.variables.push(variable);
+    }
+
+    fields
+        .initializers
+        .push(attributes.into_token_stream(field_name, &field.ty)?);
+
+    Ok(())
+}
+
+// Generates the code that registers the field variable information, used to
+// document which variables are read.
+fn generate_variable(
+    field: &Field,
+    attributes: &FieldAttributes,
+    struct_attributes: &StructAttributes,
+) -> syn::Result<Option<TokenStream>> {
+    let field_type = &field.ty;
+
+    if attributes.flatten {
+        let prefix = attributes.prefix.clone().unwrap_or_default();
+        return Ok(Some(quote! {
+            <#field_type>::collect_env_variables(&format!("{}{}", prefix, #prefix), suffix, variables);
+        }));
+    }
 
+    let Some(env_name) = attributes.env_name.as_ref() else {
+        return Ok(None);
+    };
+
+    // The value check reuses the same decoding used to load the field.
+    let value_type = FieldType::new(field_type, attributes.separator.clone())?;
+    let optional = value_type.optional;
+    let decode = value_type.kind.decode(quote! { value });
+    let skip_empty = if optional {
+        quote! {
+            if value.is_empty() || value == "None" {
+                return Ok(());
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    let delimiter = &struct_attributes.delimiter;
+    let kind = quote!(#field_type).to_string().replace(' ', "");
+    let required = attributes.required;
+    let default = match &attributes.default_value {
+        Some(default) => quote! { Some(#default.to_string()) },
+        None => quote! { None },
+    };
+
+    let description = doc_comment(&field.attrs);
+    let description = match description {
+        Some(description) => quote! { Some(#description.to_string()) },
+        None => quote! { None },
+    };
+
+    Ok(Some(quote! {
+        {
+            let name = format!("{}{}", prefix, #env_name);
+            variables.push(mikros::env::Variable {
+                suffixed_name: suffix.map(|s| format!("{}{}{}", name, #delimiter, s)),
+                name,
+                default: #default,
+                required: #required,
+                kind: #kind.to_string(),
+                description: #description,
+                optional: #optional,
+                check: Some(|key: &str, value: &str| -> Result<(), String> {
+                    #skip_empty
+                    let errors = &mut Vec::new();
+                    let _ = #decode;
+
+                    match errors.is_empty() {
+                        true => Ok(()),
+                        false => Err(errors.join("; ")),
+                    }
+                }),
+            });
+        }
+    }))
+}
 
-    Ok((initializer, default_check))
+// Joins the lines of the doc comment of a field.
+fn doc_comment(attrs: &[Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta {
+            syn::Meta::NameValue(syn::MetaNameValue {
+                value: syn::Expr::Lit(syn::ExprLit { lit: Lit::Str(v), .. }),
+                ..
+            }) => Some(v.value().trim().to_string()),
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join(" "))
+    }
 }
 
-fn parse_attribute(attr: &Attribute, field_name: &Ident) -> Result<FieldAttributes, String> {
-    let mut env_name = None;
-    let mut default_value = None;
+fn parse_attribute(attr: &Attribute, field_name: &Ident) -> syn::Result<FieldAttributes> {
+    let mut attributes = FieldAttributes::default();
 
     let result = attr.parse_nested_meta(|meta| {
         if meta.path.is_ident("variable") {
             if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
-                env_name = Some(v.value());
+                attributes.env_name = Some(v.value());
             }
         } else if meta.path.is_ident("default") {
             if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
-                default_value = Some(v.value());
+                attributes.default_value = Some(v.value());
+            }
+        } else if meta.path.is_ident("separator") {
+            if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
+                attributes.separator = Some(v.value());
+            }
+        } else if meta.path.is_ident("prefix") {
+            if let Ok(Lit::Str(v)) = meta.value()?.parse::<Lit>() {
+                attributes.prefix = Some(v.value());
             }
+        } else if meta.path.is_ident("required") {
+            attributes.required = true;
+        } else if meta.path.is_ident("flatten") {
+            attributes.flatten = true;
         }
 
         Ok(())
     });
 
     if result.is_err() {
-        return Err(format!(
-            "failed to parse 'env' attribute on field '{field_name}'"
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("failed to parse 'env' attribute on field '{field_name}'"),
+        ));
+    }
+
+    if attributes.flatten {
+        if attributes.env_name.is_some() || attributes.default_value.is_some() || attributes.required {
+            return Err(syn::Error::new_spanned(
+                attr,
+                format!("'flatten' env field '{field_name}' cannot have 'variable', 'default' or 'required' attributes"),
+            ));
+        }
+
+        return Ok(attributes);
+    }
+
+    if attributes.prefix.is_some() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("'prefix' attribute can only be used with 'flatten' env field '{field_name}'"),
         ));
     }
 
-    if env_name.is_some() && default_value.is_none() {
-        return Err(format!(
-            "'default' attribute is mandatory for env field '{field_name}'"
+    if attributes.required && attributes.default_value.is_some() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("'required' env field '{field_name}' cannot have a 'default' attribute"),
         ));
     }
 
-    Ok(FieldAttributes {
-        env_name,
-        default_value,
-    })
+    if attributes.env_name.is_some() && attributes.default_value.is_none() && !attributes.required {
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("'default' attribute is mandatory for env field '{field_name}' unless it is 'required'"),
+        ));
+    }
+
+    Ok(attributes)
 }
 
-fn generate_default_check(field_name: &Ident, default_value: &Option<String>, field_type: &syn::Type) -> TokenStream {
-    let is_option = match field_type {
-        syn::Type::Path(type_path) if type_path.path.segments.len() == 1 => {
-            type_path.path.segments[0].ident == "Option"
-        }
-        _ => false,
-    };
+fn generate_default_check(field_name: &Ident, attributes: &FieldAttributes, field_type: &syn::Type) -> syn::Result<TokenStream> {
+    let check = match (&attributes.env_name, &attributes.default_value) {
+        (Some(_), Some(default)) => {
+            let ty = FieldType::new(field_type, attributes.separator.clone())?;
 
-    let check = if let Some(default) = default_value {
-        if is_option {
-            if default == "None" {
+            if ty.optional && default == "None" {
                 quote! { self.#field_name.is_none() }
+            } else if ty.kind.is_scalar() {
+                if ty.optional {
+                    quote! { self.#field_name.as_ref().map(|v| v.to_string()) == Some(#default.to_string()) }
+                } else {
+                    quote! { self.#field_name.to_string() == #default }
+                }
             } else {
-                quote! { self.#field_name.as_ref().map(|v| v.to_string()) == Some(#default.to_string()) }
+                // Lists and durations are compared using their decoded
+                // default value.
+                let decode = ty.kind.decode(quote! { #default });
+                let current = if ty.optional {
+                    quote! { self.#field_name.as_ref() }
+                } else {
+                    quote! { Some(&self.#field_name) }
+                };
+
+                quote! {
+                    {
+                        let key = stringify!(#field_name).to_string();
+                        let errors = &mut Vec::new();
+                        #decode.as_ref() == #current
+                    }
+                }
             }
-        } else {
-            quote! { self.#field_name.to_string() == #default }
         }
-    } else {
-        quote! { false } // No default specified
+        _ => quote! { false }, // No default specified
     };
 
-    quote! {
+    Ok(quote! {
         (stringify!(#field_name), #check)
-    }
-}
\ No newline at end of file
+    })
+}
@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Attribute, DeriveInput, Fields, Lit, Variant};
+
+// The error kinds supported by the catalog, named after the ServiceError API
+// that creates each one.
+const KINDS: [&str; 11] = [
+    "internal",
+    "not_found",
+    "invalid_arguments",
+    "precondition_failed",
+    "custom",
+    "permission_denied",
+    "unauthenticated",
+    "already_exists",
+    "rate_limited",
+    "unavailable",
+    "timeout",
+];
+
+// The range of valid HTTP status codes.
+const HTTP_STATUS_RANGE: std::ops::RangeInclusive<u16> = 100..=599;
+
+pub fn generate(input: DeriveInput) -> syn::Result<TokenStream> {
+    let enum_name = input.ident.clone();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let syn::Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "ErrorCatalog can only be derived for enums",
+        ));
+    };
+
+    let mut codes = HashSet::new();
+    let mut definitions = Vec::new();
+    let mut definition_arms = Vec::new();
+    let mut message_arms = Vec::new();
+
+    for variant in &data.variants {
+        let attributes = parse_attributes(variant)?;
+
+        if !codes.insert(attributes.code) {
+            return Err(syn::Error::new_spanned(
+                &variant.ident,
+                format!(
+                    "duplicated error code {} on variant '{}'",
+                    attributes.code, variant.ident
+                ),
+            ));
+        }
+
+        let name = variant.ident.to_string();
+        let variant_name = &variant.ident;
+        let code = attributes.code;
+        let kind = &attributes.kind;
+        let message = &attributes.message;
+        let http_status = match attributes.http_status {
+            Some(status) => quote! { Some(#status) },
+            None => quote! { None },
+        };
+
+        let definition = quote! {
+            mikros::errors::ErrorDefinition {
+                name: #name,
+                code: #code,
+                kind: #kind,
+                http_status: #http_status,
+                message: #message,
+            }
+        };
+
+        definition_arms.push(quote! {
+            Self::#variant_name { .. } => #definition
+        });
+
+        definitions.push(definition);
+
+        message_arms.push(message_arm(variant, message)?);
+    }
+
+    Ok(quote! {
+        impl #impl_generics mikros::errors::ErrorCatalog for #enum_name #ty_generics #where_clause {
+            fn definitions() -> Vec<mikros::errors::ErrorDefinition> {
+                vec![
+                    #(#definitions),*
+                ]
+            }
+
+            fn definition(&self) -> mikros::errors::ErrorDefinition {
+                match self {
+                    #(#definition_arms),*
+                }
+            }
+
+            fn message(&self) -> String {
+                match self {
+                    #(#message_arms),*
+                }
+            }
+        }
+    })
+}
+
+struct CatalogAttributes {
+    code: i32,
+    kind: String,
+    message: String,
+    http_status: Option<u16>,
+}
+
+fn parse_attributes(variant: &Variant) -> syn::Result<CatalogAttributes> {
+    let name = &variant.ident;
+    let Some(attr) = variant.attrs.iter().find(|a| a.path().is_ident("catalog")) else {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!("missing 'catalog' attribute on variant '{name}'"),
+        ));
+    };
+
+    let (code, kind, message, http_status) = parse_attribute(attr)?;
+
+    let Some(code) = code else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("'code' attribute is mandatory for variant '{name}'"),
+        ));
+    };
+
+    let Some(message) = message else {
+        return Err(syn::Error::new_spanned(
+            attr,
+            format!("'message' attribute is mandatory for variant '{name}'"),
+        ));
+    };
+
+    let kind = kind.unwrap_or_else(|| "custom".to_string());
+
+    Ok(CatalogAttributes {
+        code,
+        kind,
+        message,
+        http_status,
+    })
+}
+
+type ParsedAttribute = (Option<i32>, Option<String>, Option<String>, Option<u16>);
+
+fn parse_attribute(attr: &Attribute) -> syn::Result<ParsedAttribute> {
+    let mut code = None;
+    let mut kind = None;
+    let mut message = None;
+    let mut http_status = None;
+
+    attr.parse_nested_meta(|meta| {
+        let value = meta.value()?.parse::<Lit>()?;
+
+        match value {
+            Lit::Int(v) if meta.path.is_ident("code") => code = Some(v.base10_parse()?),
+            Lit::Int(v) if meta.path.is_ident("http_status") => {
+                let status = v.base10_parse::<u16>()?;
+                if !HTTP_STATUS_RANGE.contains(&status) {
+                    return Err(syn::Error::new_spanned(
+                        v,
+                        "'http_status' must be a valid HTTP status code, between 100 and 599",
+                    ));
+                }
+
+                http_status = Some(status)
+            }
+            Lit::Str(v) if meta.path.is_ident("kind") => {
+                if !KINDS.contains(&v.value().as_str()) {
+                    return Err(syn::Error::new_spanned(
+                        &v,
+                        format!(
+                            "unsupported kind '{}', expected one of: {}",
+                            v.value(),
+                            KINDS.join(", ")
+                        ),
+                    ));
+                }
+
+                kind = Some(v.value())
+            }
+            Lit::Str(v) if meta.path.is_ident("message") => message = Some(v.value()),
+            _ => return Err(meta.error("unsupported catalog attribute")),
+        }
+
+        Ok(())
+    })?;
+
+    Ok((code, kind, message, http_status))
+}
+
+// A part of a message template.
+enum Piece {
+    Text(String),
+    Field(String),
+}
+
+// Splits a message template into text and `{field}` placeholders, where
+// `{{` and `}}` are escaped braces.
+fn parse_template(template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(format!("unclosed placeholder in '{template}'")),
+                    }
+                }
+
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+
+                pieces.push(Piece::Field(field.trim().to_string()));
+            }
+            '}' => return Err(format!("unexpected '}}' in '{template}'")),
+            c => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+
+    Ok(pieces)
+}
+
+// Generates the match arm that builds the variant message, binding only the
+// fields used by its template.
+fn message_arm(variant: &Variant, template: &str) -> syn::Result<TokenStream> {
+    let variant_name = &variant.ident;
+    let pieces = parse_template(template).map_err(|e| syn::Error::new_spanned(variant_name, e))?;
+    let used: HashSet<&str> = pieces
+        .iter()
+        .filter_map(|p| match p {
+            Piece::Field(field) => Some(field.as_str()),
+            Piece::Text(_) => None,
+        })
+        .collect();
+
+    let (pattern, names): (TokenStream, Vec<String>) = match &variant.fields {
+        Fields::Named(fields) => {
+            let names: Vec<String> = fields
+                .named
+                .iter()
+                .filter_map(|f| f.ident.as_ref().map(|i| i.to_string()))
+                .collect();
+
+            let bindings = fields
+                .named
+                .iter()
+                .filter_map(|f| f.ident.as_ref())
+                .filter(|i| used.contains(i.to_string().as_str()));
+
+            (quote! { Self::#variant_name { #(#bindings,)* .. } }, names)
+        }
+        Fields::Unnamed(fields) => {
+            let names: Vec<String> = (0..fields.unnamed.len()).map(|i| i.to_string()).collect();
+            let bindings = names.iter().map(|i| {
+                if used.contains(i.as_str()) {
+                    let binding = format_ident!("field_{}", i);
+                    quote! { #binding }
+                } else {
+                    quote! { _ }
+                }
+            });
+
+            (quote! { Self::#variant_name(#(#bindings),*) }, names)
+        }
+        Fields::Unit => (quote! { Self::#variant_name }, Vec::new()),
+    };
+
+    let mut parts = Vec::new();
+    for piece in &pieces {
+        match piece {
+            Piece::Text(text) => parts.push(quote! { String::from(#text) }),
+            Piece::Field(field) => {
+                if !names.contains(field) {
+                    return Err(syn::Error::new_spanned(
+                        variant_name,
+                        format!("unknown field '{field}' in message of variant '{variant_name}'"),
+                    ));
+                }
+
+                let binding = match &variant.fields {
+                    Fields::Unnamed(_) => format_ident!("field_{}", field),
+                    _ => format_ident!("{}", field),
+                };
+
+                parts.push(quote! { #binding.to_string() });
+            }
+        }
+    }
+
+    Ok(quote! {
+        #pattern => [#(#parts),*].concat()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_http_status() {
+        let input: DeriveInput = syn::parse_quote! {
+            enum OrderError {
+                #[catalog(code = 1, message = "declined", http_status = 99)]
+                Declined,
+            }
+        };
+
+        let error = generate(input).unwrap_err();
+        assert!(error.to_string().contains("between 100 and 599"));
+
+        let input: DeriveInput = syn::parse_quote! {
+            enum OrderError {
+                #[catalog(code = 1, message = "declined", http_status = 600)]
+                Declined,
+            }
+        };
+
+        assert!(generate(input).is_err());
+
+        let input: DeriveInput = syn::parse_quote! {
+            enum OrderError {
+                #[catalog(code = 1, message = "declined", http_status = 402)]
+                Declined,
+            }
+        };
+
+        assert!(generate(input).is_ok());
+    }
+
+    #[test]
+    fn test_errors_are_compile_errors() {
+        let input: DeriveInput = syn::parse_quote! {
+            enum OrderError {
+                #[catalog(code = 1, message = "order {id} not found")]
+                NotFound,
+
+                #[catalog(code = 1, message = "declined")]
+                Declined,
+            }
+        };
+
+        let error = generate(input).unwrap_err();
+        assert!(error.to_string().contains("unknown field 'id'"));
+
+        let input: DeriveInput = syn::parse_quote! {
+            enum OrderError {
+                #[catalog(code = 1, message = "declined", kind = "teapot")]
+                Declined,
+            }
+        };
+
+        let error = generate(input).unwrap_err();
+        assert!(error.to_string().contains("unsupported kind 'teapot'"));
+        assert!(
+            error
+                .to_compile_error()
+                .to_string()
+                .contains("compile_error")
+        );
+    }
+}
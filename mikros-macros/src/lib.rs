@@ -1,4 +1,5 @@
 mod env;
+mod error_catalog;
 mod lifecycle;
 
 use proc_macro::TokenStream;
@@ -11,14 +12,43 @@ use syn::{parse_macro_input, DeriveInput};
 /// sub-attributes:
 ///
 /// - variable: required attribute which sets the variable name that will be
-///             used to set the member.
+///   used to set the member.
 /// - default: required attribute to set the default value in case the variable
-///             is not found.
+///   is not found, unless the member is `required`.
+/// - required: marks the variable as mandatory, without a default value.
+/// - separator: the string used to split `Vec<T>` members values (default is
+///   ",").
+/// - flatten: loads the member, which must also derive `Env`, from its own
+///   variables. An optional `prefix` is added to their names.
+///
+/// `std::time::Duration` members accept values like "500ms", "30s" or "1h30m".
+///
+/// At the struct level, `suffix_delimiter` sets the delimiter used with
+/// suffixed variables and `prefix` sets a prefix added to every variable name.
+///
+/// `from_env` panics when a variable has an invalid value or a required one is
+/// not set, while `try_from_env` returns all of them as errors. Optional
+/// (`Option<T>`) fields with invalid values are only errors for the latter,
+/// `from_env` leaves them as `None`.
+///
+/// `try_from_env_with_suffix` reads each variable with a suffix first, like
+/// `VAR_NAME_my_service`, falling back to its name without it and then to its
+/// default value. Required variables are satisfied by either name.
+///
+/// `env_variables` returns information about every variable read by the
+/// structure, like its name, default value, type and the member doc comment,
+/// as `mikros::env::Variable` items.
 /// ```ignore
 /// use mikros_macros::Env;
 ///
 /// #[derive(Env, Debug)]
-/// #[env(suffix_delimiter = "_")]
+/// pub struct Database {
+///     #[env(variable = "HOST", default = "localhost")]
+///     host: String,
+/// }
+///
+/// #[derive(Env, Debug)]
+/// #[env(suffix_delimiter = "_", prefix = "APP_")]
 /// pub struct Example {
 ///     #[env(variable = "VAR_NAME", default = "value")]
 ///     name: String,
@@ -29,19 +59,34 @@ use syn::{parse_macro_input, DeriveInput};
 ///     #[env(variable = "VAR_OPTIONAL", default = "None")]
 ///     opt: Option<String>,
 ///
+///     #[env(variable = "VAR_TOKEN", required)]
+///     token: String,
+///
+///     #[env(variable = "VAR_HOSTS", default = "a;b", separator = ";")]
+///     hosts: Vec<String>,
+///
+///     #[env(variable = "VAR_TIMEOUT", default = "30s")]
+///     timeout: std::time::Duration,
+///
+///     // Loaded from APP_DB_HOST
+///     #[env(flatten, prefix = "DB_")]
+///     database: Database,
+///
 ///     // A member that won't be loaded from environment values
 ///     no_env_loaded: bool,
 /// }
 ///
 /// pub fn foo() {
-///     let e = Example::from_env();
-///     println!("{e}");
+///     match Example::try_from_env() {
+///         Ok(e) => println!("{e:?}"),
+///         Err(errors) => println!("{}", errors.join("\n")),
+///     }
 /// }
 /// ```
 #[proc_macro_derive(Env, attributes(env))]
 pub fn derive_env_impl(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
-    let gen = env::generate(input);
+    let gen = env::generate(input).unwrap_or_else(|e| e.to_compile_error());
     TokenStream::from(gen)
 }
 
@@ -53,3 +98,42 @@ pub fn derive_lifecycle_impl(input: TokenStream) -> TokenStream {
     let gen = lifecycle::generate(input);
     TokenStream::from(gen)
 }
+
+/// ErrorCatalog implements the `mikros::errors::ErrorCatalog` trait for an
+/// enum of application errors.
+///
+/// Each variant must have a `catalog` attribute with the following
+/// sub-attributes:
+///
+/// - code: required attribute with the stable error code, which must be
+///   unique inside the enum.
+/// - message: required attribute with the error message template, where
+///   `{field}` (or `{0}` for tuple variants) is replaced by the field value.
+/// - kind: the error kind, named after the `ServiceError` API that creates it,
+///   like "not_found" or "precondition_failed". Default is "custom".
+/// - http_status: the HTTP status code used instead of the kind default one,
+///   which must be between 100 and 599.
+///
+/// ```ignore
+/// use mikros::errors::ErrorCatalog;
+/// use mikros_macros::ErrorCatalog;
+///
+/// #[derive(ErrorCatalog)]
+/// pub enum OrderError {
+///     #[catalog(code = 1001, kind = "not_found", message = "order {id} not found")]
+///     NotFound { id: String },
+///
+///     #[catalog(code = 1002, message = "payment declined: {0}", http_status = 402)]
+///     PaymentDeclined(String),
+/// }
+///
+/// pub fn foo(ctx: Arc<Context>) -> mikros::errors::Result<()> {
+///     Err(OrderError::NotFound { id: "42".to_string() }.into_service_error(ctx))
+/// }
+/// ```
+#[proc_macro_derive(ErrorCatalog, attributes(catalog))]
+pub fn derive_error_catalog_impl(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let gen = error_catalog::generate(input).unwrap_or_else(|e| e.to_compile_error());
+    TokenStream::from(gen)
+}
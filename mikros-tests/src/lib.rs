@@ -1,2 +1,2 @@
 mod macros;
-mod common;
\ No newline at end of file
+pub mod common;
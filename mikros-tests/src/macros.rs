@@ -1,2 +1,3 @@
 mod lifecycle;
-mod env;
\ No newline at end of file
+mod env;
+mod error_catalog;
\ No newline at end of file
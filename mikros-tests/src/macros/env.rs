@@ -7,6 +7,7 @@ mod tests {
     use mikros_macros::Env;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_struct_load_from_env() {
         #[derive(Env, Debug)]
         struct Example {
@@ -56,6 +57,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_struct_load_from_env_with_suffix() {
         #[derive(Env, Debug)]
         #[env(suffix_delimiter = "_")]
@@ -88,6 +90,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_struct_with_default_values() {
         #[derive(Env, Debug)]
         struct Example {
@@ -111,6 +114,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_struct_without_env_attributes() {
         #[derive(Env, Debug)]
         struct Example {
@@ -126,4 +130,229 @@ mod tests {
         assert_eq!(e.limit, 0);
         assert_eq!(e.unused, false);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_struct_with_lists_and_durations() {
+        #[derive(Env, Debug)]
+        struct Example {
+            #[env(variable = "TEST_4_HOSTS", default = "a,b")]
+            hosts: Vec<String>,
+
+            #[env(variable = "TEST_4_PORTS", default = "", separator = ";")]
+            ports: Vec<u16>,
+
+            #[env(variable = "TEST_4_TIMEOUT", default = "30s")]
+            timeout: std::time::Duration,
+
+            #[env(variable = "TEST_4_INTERVAL", default = "None")]
+            interval: Option<std::time::Duration>,
+        }
+
+        let source = |name: &str| match name {
+            "TEST_4_PORTS" => Some("80; 443".to_string()),
+            "TEST_4_INTERVAL" => Some("1h30m".to_string()),
+            _ => None,
+        };
+
+        let e = Example::from_source(None, true, &source);
+        assert_eq!(e.hosts, vec!["a", "b"]);
+        assert_eq!(e.ports, vec![80, 443]);
+        assert_eq!(e.timeout, std::time::Duration::from_secs(30));
+        assert_eq!(e.interval, Some(std::time::Duration::from_secs(5400)));
+
+        let defaults = e.check_defaults();
+        assert!(defaults.contains(&("hosts", true)));
+        assert!(defaults.contains(&("timeout", true)));
+        assert!(defaults.contains(&("ports", false)));
+    }
+
+    #[test]
+    fn test_struct_with_prefix_and_nested_struct() {
+        #[derive(Env, Debug)]
+        struct Database {
+            /// The database host.
+            #[env(variable = "HOST", default = "localhost")]
+            host: String,
+
+            #[env(variable = "PORT", default = "5432")]
+            port: u16,
+        }
+
+        #[derive(Env, Debug)]
+        #[env(prefix = "TEST_5_")]
+        struct Example {
+            #[env(variable = "NAME", default = "example")]
+            name: String,
+
+            #[env(flatten, prefix = "DB_")]
+            database: Database,
+        }
+
+        let source = |name: &str| match name {
+            "TEST_5_NAME" => Some("service".to_string()),
+            "TEST_5_DB_HOST" => Some("db.local".to_string()),
+            "TEST_5_DB_PORT_dev" => Some("6432".to_string()),
+            _ => None,
+        };
+
+        let e = Example::from_source(None, true, &source);
+        assert_eq!(e.name, "service");
+        assert_eq!(e.database.host, "db.local");
+        assert_eq!(e.database.port, 5432);
+
+        let e = Example::from_source(Some("dev"), false, &source);
+        assert_eq!(e.name, "");
+        assert_eq!(e.database.port, 6432);
+
+        let variables = Example::env_variables(Some("dev"));
+        assert_eq!(variables.len(), 3);
+        assert_eq!(variables[1].name, "TEST_5_DB_HOST");
+        assert_eq!(variables[1].description.as_deref(), Some("The database host."));
+        assert_eq!(
+            variables[2].suffixed_name.as_deref(),
+            Some("TEST_5_DB_PORT_dev")
+        );
+        assert_eq!(variables[2].kind, "u16");
+    }
+
+    #[test]
+    fn test_try_from_source_collects_errors() {
+        #[derive(Env, Debug)]
+        struct Example {
+            #[env(variable = "TEST_6_TOKEN", required)]
+            token: String,
+
+            #[env(variable = "TEST_6_AGE", default = "42")]
+            age: i32,
+
+            #[env(variable = "TEST_6_TIMEOUT", default = "1s")]
+            timeout: std::time::Duration,
+
+            #[env(variable = "TEST_6_LIMIT", default = "None")]
+            limit: Option<u32>,
+        }
+
+        let source = |name: &str| match name {
+            "TEST_6_AGE" => Some("old".to_string()),
+            "TEST_6_TIMEOUT" => Some("10 parsecs".to_string()),
+            "TEST_6_LIMIT" => Some("-1".to_string()),
+            _ => None,
+        };
+
+        let errors = Example::try_from_source(None, true, &source).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("TEST_6_TOKEN: required"));
+        assert!(errors[1].starts_with("TEST_6_AGE: invalid value"));
+        assert!(errors[2].starts_with("TEST_6_TIMEOUT: invalid duration"));
+        assert!(errors[3].starts_with("TEST_6_LIMIT: invalid value"));
+
+        // Required variables are only checked when defaults are used.
+        let e = Example::try_from_source(Some("dev"), false, &source).unwrap();
+        assert_eq!(e.token, "");
+
+        let source = |name: &str| (name == "TEST_6_TOKEN").then(|| "secret".to_string());
+        let e = Example::try_from_source(None, true, &source).unwrap();
+        assert_eq!(e.token, "secret");
+        assert_eq!(e.age, 42);
+        assert_eq!(e.timeout, std::time::Duration::from_secs(1));
+        assert_eq!(e.limit, None);
+
+        // Loaders that do not return errors leave invalid optional values unset.
+        let source = |name: &str| match name {
+            "TEST_6_TOKEN" => Some("secret".to_string()),
+            "TEST_6_LIMIT" => Some("-1".to_string()),
+            _ => None,
+        };
+
+        let e = Example::from_source(None, true, &source);
+        assert_eq!(e.limit, None);
+    }
+
+    #[test]
+    fn test_try_from_source_with_suffix() {
+        #[derive(Env, Debug)]
+        #[env(prefix = "TEST_7_")]
+        struct Database {
+            #[env(variable = "HOST", default = "localhost")]
+            host: String,
+        }
+
+        #[derive(Env, Debug)]
+        struct Example {
+            #[env(variable = "TEST_7_TOKEN", required)]
+            token: String,
+
+            #[env(variable = "TEST_7_PORT", default = "7070")]
+            port: u16,
+
+            #[env(variable = "TEST_7_LIMIT", default = "None")]
+            limit: Option<u32>,
+
+            #[env(flatten)]
+            database: Database,
+        }
+
+        // Required variables set only with the suffix are found.
+        let source = |name: &str| match name {
+            "TEST_7_TOKEN_my_service" => Some("secret".to_string()),
+            "TEST_7_PORT" => Some("8080".to_string()),
+            "TEST_7_PORT_my_service" => Some("0".to_string()),
+            "TEST_7_LIMIT" => Some("10".to_string()),
+            "TEST_7_LIMIT_my_service" => Some("".to_string()),
+            "TEST_7_HOST" => Some("db".to_string()),
+            _ => None,
+        };
+
+        let e = Example::try_from_source_with_suffix("my_service", &source).unwrap();
+        assert_eq!(e.token, "secret");
+        assert_eq!(e.port, 0);
+        assert_eq!(e.limit, Some(10));
+        assert_eq!(e.database.host, "db");
+
+        // Variables without the suffix are used when the suffixed ones are
+        // not set, and defaults when neither is.
+        let source = |name: &str| (name == "TEST_7_TOKEN").then(|| "plain".to_string());
+        let e = Example::try_from_source_with_suffix("my_service", &source).unwrap();
+        assert_eq!(e.token, "plain");
+        assert_eq!(e.port, 7070);
+        assert_eq!(e.limit, None);
+        assert_eq!(e.database.host, "localhost");
+
+        let errors = Example::try_from_source_with_suffix("my_service", &|_| None).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("TEST_7_TOKEN: required"));
+    }
+
+    #[test]
+    fn test_env_variables_check_values() {
+        #[allow(dead_code)]
+        #[derive(Env, Debug)]
+        struct Example {
+            #[env(variable = "TEST_8_PORT", default = "8080")]
+            port: u16,
+
+            #[env(variable = "TEST_8_LIMIT", default = "None")]
+            limit: std::option::Option<u32>,
+
+            #[env(variable = "TEST_8_PORTS", default = "80,443")]
+            ports: Vec<u16>,
+        }
+
+        let variables = Example::env_variables(None);
+        assert!(!variables[0].optional);
+        assert!(variables[1].optional);
+
+        let check = |index: usize, value: &str| {
+            let variable = &variables[index];
+            variable.check.unwrap()(&variable.name, value)
+        };
+
+        assert!(check(0, "8080").is_ok());
+        assert!(check(0, "abc").unwrap_err().starts_with("TEST_8_PORT: invalid value"));
+        assert!(check(1, "None").is_ok());
+        assert!(check(1, "").is_ok());
+        assert!(check(1, "-1").unwrap_err().starts_with("TEST_8_LIMIT: invalid value"));
+        assert!(check(2, "80, 443").is_ok());
+        assert!(check(2, "80,http").is_err());
+    }
+}
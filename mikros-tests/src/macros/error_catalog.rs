@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests {
+    use mikros::errors::ErrorCatalog;
+    use mikros_macros::ErrorCatalog;
+
+    #[allow(dead_code)]
+    #[derive(ErrorCatalog)]
+    enum Example {
+        #[catalog(
+            code = 1,
+            kind = "invalid_arguments",
+            message = "invalid {field}: {reason}"
+        )]
+        Invalid {
+            field: String,
+            reason: String,
+            value: i64,
+        },
+
+        #[catalog(
+            code = 2,
+            kind = "unavailable",
+            message = "{1} is down, retry in {{{0}}}s"
+        )]
+        Unavailable(u64, String),
+
+        #[catalog(code = 3, message = "nothing to interpolate")]
+        Plain,
+    }
+
+    #[test]
+    fn test_error_catalog_messages() {
+        let error = Example::Invalid {
+            field: "name".to_string(),
+            reason: "too short".to_string(),
+            value: 0,
+        };
+
+        assert_eq!(error.message(), "invalid name: too short");
+        assert_eq!(error.definition().code, 1);
+        assert_eq!(
+            Example::Unavailable(5, "db".to_string()).message(),
+            "db is down, retry in {5}s"
+        );
+        assert_eq!(Example::Plain.message(), "nothing to interpolate");
+        assert_eq!(Example::Plain.definition().kind, "custom");
+    }
+
+    #[test]
+    fn test_error_catalog_export() {
+        let definitions = Example::definitions();
+        assert_eq!(definitions.len(), 3);
+        assert_eq!(definitions[1].name, "Unavailable");
+
+        let catalog = Example::catalog();
+        assert_eq!(catalog[0]["kind"], "ValidationError");
+        assert_eq!(catalog[0]["http_status"], 400);
+        assert_eq!(catalog[1]["kind"], "UnavailableError");
+        assert_eq!(catalog[1]["http_status"], 503);
+        assert_eq!(catalog[2]["kind"], "CustomError");
+    }
+}
@@ -5,6 +5,7 @@ mod tests {
     use mikros_macros::Lifecycle;
 
     #[tokio::test]
+    #[allow(clippy::let_unit_value)]
     async fn test_lifecycle_trait_ok() {
         #[allow(dead_code)]
         #[derive(Lifecycle, Clone)]
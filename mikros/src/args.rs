@@ -1,17 +1,62 @@
+use std::collections::HashMap;
+
+/// A custom command line flag, registered by the service through the
+/// `ServiceBuilder::with_flag` API.
+#[derive(Clone, Debug)]
+pub struct Flag {
+    name: String,
+    value_name: Option<String>,
+    description: String,
+}
+
+impl Flag {
+    /// Creates a flag without value, like `--dry-run`.
+    pub fn new(name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value_name: None,
+            description: description.to_string(),
+        }
+    }
+
+    /// Creates a flag that requires a value, like `--workers <n>`.
+    pub fn with_value(name: &str, value_name: &str, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            value_name: Some(value_name.to_string()),
+            description: description.to_string(),
+        }
+    }
+
+    fn usage(&self) -> String {
+        match &self.value_name {
+            None => self.name.clone(),
+            Some(value) => format!("{} <{value}>", self.name),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct Args {
-    pub config_path: Option<String>,
+    pub config_paths: Vec<String>,
+    pub check_config: bool,
+    pub print_schema: bool,
+    pub print_config: bool,
+    pub print_env: Option<String>,
+    pub version: bool,
+    pub overrides: Vec<(String, String)>,
+    pub flags: HashMap<String, Option<String>>,
     help: bool,
     service_name: String,
 }
 
 impl Args {
-    pub fn load() -> Self {
+    pub fn load(flags: &[Flag]) -> Self {
         let args: Vec<String> = std::env::args().collect();
-        match Self::parse(&args) {
+        match Self::parse(&args, flags) {
             Ok(args) => {
                 if args.help {
-                    Self::usage(&args.service_name);
+                    Self::usage(&args.service_name, flags);
                     std::process::exit(0);
                 }
 
@@ -24,10 +69,17 @@ impl Args {
         }
     }
 
-    fn parse(args: &[String]) -> Result<Self, String> {
+    fn parse(args: &[String], flags: &[Flag]) -> Result<Self, String> {
         let mut config = Args {
             help: false,
-            config_path: None,
+            config_paths: Vec::new(),
+            check_config: false,
+            print_schema: false,
+            print_config: false,
+            print_env: None,
+            version: false,
+            overrides: Vec::new(),
+            flags: HashMap::new(),
             service_name: args[0].clone(),
         };
 
@@ -42,15 +94,66 @@ impl Args {
                 "-h" | "--help" => {
                     config.help = true;
                 }
+                "--version" => {
+                    config.version = true;
+                }
+                "--check-config" => {
+                    config.check_config = true;
+                }
+                "--print-schema" => {
+                    config.print_schema = true;
+                }
+                "--print-config" => {
+                    config.print_config = true;
+                }
+                "--print-env" => {
+                    // The output format is optional and defaults to a table.
+                    let format = match iter.peek() {
+                        Some((_, next_arg)) if *next_arg == "table" || *next_arg == "json" => {
+                            let format = (*next_arg).clone();
+                            iter.next();
+                            format
+                        }
+                        _ => "table".to_string(),
+                    };
+
+                    config.print_env = Some(format);
+                }
                 "--config" => match iter.peek() {
                     None => return Err("error: --config option requires a file path".to_string()),
                     Some((_, next_arg)) => {
-                        config.config_path = Some((*next_arg).to_string());
+                        config.config_paths.push((*next_arg).to_string());
+                        iter.next();
+                    }
+                },
+                "--set" => match iter
+                    .peek()
+                    .and_then(|(_, next_arg)| next_arg.split_once('='))
+                {
+                    Some((key, value)) if !key.is_empty() => {
+                        config.overrides.push((key.to_string(), value.to_string()));
                         iter.next();
                     }
+                    _ => {
+                        return Err(
+                            "error: --set option requires a key.path=value argument".to_string()
+                        );
+                    }
                 },
                 _ => {
-                    return Err(format!("unknown argument: {arg}"));
+                    let Some(flag) = flags.iter().find(|f| f.name == *arg) else {
+                        return Err(format!("unknown argument: {arg}"));
+                    };
+
+                    let value = match &flag.value_name {
+                        None => None,
+                        Some(_) => match iter.next() {
+                            None => return Err(format!("error: {arg} option requires a value")),
+                            Some((_, next_arg)) => Some(next_arg.clone()),
+                        },
+                    };
+
+                    config.flags.insert(flag.name.clone(), value);
                 }
             }
         }
@@ -58,12 +161,49 @@ impl Args {
         Ok(config)
     }
 
-    fn usage(service_name: &str) {
+    fn usage(service_name: &str, flags: &[Flag]) {
         println!("Usage: {service_name} [OPTIONS]");
         println!();
         println!("Options:");
-        println!("  -h, --help      Print this help menu.");
-        println!("  --config <path> Specify an alternative 'service.toml' config file.");
+        Self::option("-h, --help", "Print this help menu.");
+        Self::option("--version", "Print the service version and exit.");
+        Self::option(
+            "--config <path>",
+            "Specify an alternative 'service.toml' config file. It can be",
+        );
+        Self::option(
+            "",
+            "repeated to layer files, with later ones overriding earlier.",
+        );
+        Self::option(
+            "--set <key=value>",
+            "Override a service.toml setting, like 'log.level=debug'.",
+        );
+        Self::option(
+            "--check-config",
+            "Validate the service definitions and exit.",
+        );
+        Self::option(
+            "--print-config",
+            "Print the effective service settings and exit.",
+        );
+        Self::option(
+            "--print-env [fmt]",
+            "Print the environment variables read by the service and exit.",
+        );
+        Self::option("", "The format can be 'table' (default) or 'json'.");
+        Self::option(
+            "--print-schema",
+            "Print the JSON Schema of the service definitions and exit.",
+        );
+
+        for flag in flags {
+            Self::option(&flag.usage(), &flag.description);
+        }
+    }
+
+    fn option(usage: &str, description: &str) {
+        println!("  {usage:<19} {description}");
     }
 }
 
@@ -72,17 +212,19 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_no_args() {
         let args = vec!["service".to_string()];
-        let result = Args::parse(&args);
+        let result = Args::parse(&args, &[]);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().help, false);
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_help() {
         let args = vec!["service".to_string(), "--help".to_string()];
-        let result = Args::parse(&args);
+        let result = Args::parse(&args, &[]);
         assert!(result.is_ok());
         assert_eq!(result.unwrap().help, true);
     }
@@ -94,18 +236,122 @@ mod tests {
             "--config".to_string(),
             "/path/to/service.toml".to_string(),
         ];
-        let result = Args::parse(&args);
+        let result = Args::parse(&args, &[]);
         assert!(result.is_ok());
+        assert_eq!(result.unwrap().config_paths, vec!["/path/to/service.toml"]);
+    }
+
+    #[test]
+    fn test_layered_config_option() {
+        let args = vec![
+            "service".to_string(),
+            "--config".to_string(),
+            "service.toml".to_string(),
+            "--config".to_string(),
+            "service.prod.toml".to_string(),
+        ];
+        let result = Args::parse(&args, &[]);
+        assert!(result.is_ok());
+        assert_eq!(
+            result.unwrap().config_paths,
+            vec!["service.toml", "service.prod.toml"]
+        );
+    }
+
+    #[test]
+    fn test_check_config_options() {
+        let args = vec![
+            "service".to_string(),
+            "--check-config".to_string(),
+            "--config".to_string(),
+            "service.toml".to_string(),
+        ];
+        let result = Args::parse(&args, &[]).unwrap();
+        assert!(result.check_config);
+        assert!(!result.print_schema);
+
+        let args = vec!["service".to_string(), "--print-schema".to_string()];
+        assert!(Args::parse(&args, &[]).unwrap().print_schema);
+    }
+
+    #[test]
+    fn test_print_env_option() {
+        let args = vec!["service".to_string(), "--print-env".to_string()];
+        let result = Args::parse(&args, &[]).unwrap();
+        assert_eq!(result.print_env.as_deref(), Some("table"));
+
+        let args = vec![
+            "service".to_string(),
+            "--print-env".to_string(),
+            "json".to_string(),
+        ];
+        let result = Args::parse(&args, &[]).unwrap();
+        assert_eq!(result.print_env.as_deref(), Some("json"));
+
+        let args = vec![
+            "service".to_string(),
+            "--print-env".to_string(),
+            "--version".to_string(),
+        ];
+        let result = Args::parse(&args, &[]).unwrap();
+        assert_eq!(result.print_env.as_deref(), Some("table"));
+        assert!(result.version);
+    }
+
+    #[test]
+    fn test_set_option() {
+        let args = vec![
+            "service".to_string(),
+            "--set".to_string(),
+            "log.level=debug".to_string(),
+            "--set".to_string(),
+            "service.items=[1, 2]".to_string(),
+        ];
+        let result = Args::parse(&args, &[]).unwrap();
+        assert_eq!(
+            result.overrides,
+            vec![
+                ("log.level".to_string(), "debug".to_string()),
+                ("service.items".to_string(), "[1, 2]".to_string()),
+            ]
+        );
+
+        let args = vec![
+            "service".to_string(),
+            "--set".to_string(),
+            "log.level".to_string(),
+        ];
+        assert!(Args::parse(&args, &[]).is_err());
+    }
+
+    #[test]
+    fn test_custom_flags() {
+        let flags = vec![
+            Flag::new("--dry-run", "Do not change anything."),
+            Flag::with_value("--workers", "n", "Number of workers."),
+        ];
+
+        let args = vec![
+            "service".to_string(),
+            "--workers".to_string(),
+            "4".to_string(),
+            "--dry-run".to_string(),
+        ];
+        let result = Args::parse(&args, &flags).unwrap();
+        assert_eq!(result.flags.get("--workers"), Some(&Some("4".to_string())));
+        assert_eq!(result.flags.get("--dry-run"), Some(&None));
+
+        let args = vec!["service".to_string(), "--workers".to_string()];
         assert_eq!(
-            result.unwrap().config_path.unwrap(),
-            "/path/to/service.toml"
+            Args::parse(&args, &flags).unwrap_err(),
+            "error: --workers option requires a value"
         );
     }
 
     #[test]
     fn test_missing_config_path_option() {
         let args = vec!["service".to_string(), "--config".to_string()];
-        let result = Args::parse(&args);
+        let result = Args::parse(&args, &[]);
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
@@ -116,7 +362,7 @@ mod tests {
     #[test]
     fn test_unknown_option() {
         let args = vec!["service".to_string(), "--unknown".to_string()];
-        let result = Args::parse(&args);
+        let result = Args::parse(&args, &[]);
         assert!(result.is_err());
         assert_eq!(result.err().unwrap(), "unknown argument: --unknown");
     }
@@ -1,16 +1,19 @@
+pub mod builder;
 pub(crate) mod errors;
+mod interpolation;
 mod name;
+pub(crate) mod schema;
 pub mod service;
 mod validation;
 
 use std::cmp::PartialEq;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::path::Path;
 use std::str::FromStr;
-use std::sync::Arc;
 
 use serde::de::DeserializeOwned;
-use validator::ValidateArgs;
+use validator::{Validate, ValidateArgs};
 
 use crate::definition::name::ServiceName;
 
@@ -30,6 +33,8 @@ pub struct Definitions {
     pub product: String,
     pub envs: Option<Vec<String>>,
     log: Option<Log>,
+    shutdown: Option<Shutdown>,
+    secrets: Option<Secrets>,
 
     features: Option<HashMap<String, serde_json::Value>>,
     services: Option<HashMap<String, serde_json::Value>>,
@@ -38,6 +43,15 @@ pub struct Definitions {
 
     #[serde(deserialize_with = "service::deserialize_services")]
     pub types: Vec<service::Service>,
+
+    // The original TOML content, kept to apply deployment overlays.
+    #[serde(skip)]
+    source: Option<toml::Table>,
+
+    // The content before environment variables interpolation, which is the
+    // one displayed, so values read from the environment are not exposed.
+    #[serde(skip)]
+    uninterpolated: Option<toml::Table>,
 }
 
 #[derive(serde_derive::Deserialize, Debug, Clone)]
@@ -73,6 +87,46 @@ impl Log {
     }
 }
 
+#[derive(serde_derive::Deserialize, Debug, Clone)]
+pub struct Shutdown {
+    /// The maximum amount of time, in seconds, that the whole shutdown, i.e.,
+    /// stopping service tasks and cleaning up features, has to finish once
+    /// the service is requested to stop.
+    pub drain_timeout: Option<u64>,
+}
+
+impl Default for Shutdown {
+    fn default() -> Self {
+        Shutdown {
+            drain_timeout: Some(30),
+        }
+    }
+}
+
+impl Shutdown {
+    fn merge(&mut self, other: Shutdown) {
+        if self.drain_timeout.is_none() {
+            self.drain_timeout = other.drain_timeout;
+        }
+    }
+
+    /// Returns the drain timeout as a Duration.
+    pub fn drain_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.drain_timeout.unwrap_or_default())
+    }
+}
+
+#[derive(serde_derive::Deserialize, Debug, Clone, Default)]
+pub struct Secrets {
+    /// The directory where secrets are mounted as files, one per secret.
+    pub directory: Option<String>,
+
+    /// The name of the providers used to resolve secrets, in order. By
+    /// default, the file provider (when a directory is set) and the env
+    /// provider are used before the ones from features.
+    pub providers: Option<Vec<String>>,
+}
+
 #[derive(serde_derive::Deserialize, Debug, Clone)]
 pub struct Client {
     pub host: String,
@@ -120,17 +174,215 @@ pub struct CustomServiceInfo {
 }
 
 impl Definitions {
+    // Loads and validates a single definitions file, without applying any
+    // deployment overlay.
+    #[cfg(test)]
     pub(crate) fn new(
         filename: Option<&str>,
         custom_info: Option<CustomServiceInfo>,
-    ) -> Result<Arc<Self>, errors::Error> {
-        let info: Definitions = match toml::from_str(&Self::load_service_file(filename)?) {
-            Ok(content) => content,
-            Err(e) => return Err(errors::Error::MalformedToml(e.to_string())),
-        };
+    ) -> Result<std::sync::Arc<Self>, errors::Error> {
+        let info = Self::parse(&Self::load_service_file(filename)?)?;
 
         info.validate(custom_info)?;
-        Ok(Arc::new(info))
+        Ok(std::sync::Arc::new(info))
+    }
+
+    // Loads the definitions from all files, in order, using the default
+    // 'service.toml' file when none is given. They still need to be validated
+    // after their deployment overlay is applied.
+    pub(crate) fn load(filenames: &[String]) -> Result<Self, errors::Error> {
+        if filenames.is_empty() {
+            return Self::parse(&Self::load_service_file(None)?);
+        }
+
+        Self::parse_layers(filenames)
+    }
+
+    /// Creates the service definitions from a list of layered files, like a
+    /// base file followed by an environment overlay. Keys from later files
+    /// override the ones from earlier files, while tables are merged key by
+    /// key. Like `from_toml`, definitions are only validated when the service
+    /// is built with them.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if some file could not be loaded, is not valid
+    /// TOML or if the merged content does not have the mandatory fields.
+    #[allow(clippy::result_large_err)]
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> crate::errors::Result<Self> {
+        Self::parse_layers(paths).map_err(Self::into_service_error)
+    }
+
+    /// Creates the service definitions from a TOML content, without loading
+    /// any file. They are only validated when the service is built with them,
+    /// using the `ServiceBuilder::with_definitions` API.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the content is not valid TOML or does not have
+    /// the mandatory fields.
+    #[allow(clippy::result_large_err)]
+    pub fn from_toml(content: &str) -> crate::errors::Result<Self> {
+        Self::parse(content).map_err(Self::into_service_error)
+    }
+
+    fn parse(content: &str) -> Result<Self, errors::Error> {
+        let table: toml::Table = toml::from_str(content)
+            .map_err(|e| errors::Error::MalformedToml(e.to_string()))?;
+
+        Self::from_table(table)
+    }
+
+    fn from_table(table: toml::Table) -> Result<Self, errors::Error> {
+        let mut info: Self = toml::Value::Table(table.clone())
+            .try_into()
+            .map_err(|e: toml::de::Error| errors::Error::MalformedToml(e.to_string()))?;
+
+        info.source = Some(table);
+        Ok(info)
+    }
+
+    // Merges the '[deploy.<deployment>]' section, if any, over the base
+    // definitions. Definitions created programmatically have no overlays.
+    pub(crate) fn with_deployment(self, deployment: &str) -> Result<Self, errors::Error> {
+        let Some(mut table) = self.source.clone() else {
+            return Ok(self);
+        };
+
+        let overlay = match table.get("deploy").and_then(|d| d.get(deployment)) {
+            Some(toml::Value::Table(overlay)) => overlay.clone(),
+            Some(_) => {
+                return Err(errors::Error::InvalidDefinitions(format!(
+                    "deploy.{deployment} must be a table"
+                )));
+            }
+            None => return Ok(self),
+        };
+
+        Self::merge_layer(&mut table, overlay);
+        Self::from_table(table)
+    }
+
+    // Applies values set through the command line, where each key is a
+    // dotted path inside the definitions, like 'log.level'.
+    pub(crate) fn with_overrides(
+        self,
+        overrides: &[(String, String)],
+    ) -> Result<Self, errors::Error> {
+        if overrides.is_empty() {
+            return Ok(self);
+        }
+
+        let Some(mut table) = self.source.clone() else {
+            return Ok(self);
+        };
+
+        for (key, value) in overrides {
+            Self::set_value(&mut table, key, value)?;
+        }
+
+        Self::from_table(table)
+    }
+
+    fn set_value(table: &mut toml::Table, key: &str, value: &str) -> Result<(), errors::Error> {
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.iter().any(|p| p.is_empty()) {
+            return Err(errors::Error::InvalidDefinitions(format!("invalid key '{key}'")));
+        }
+
+        let (last, parents) = parts.split_last().unwrap_or((&key, &[]));
+        let mut current = table;
+
+        for part in parents {
+            current = match current
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            {
+                toml::Value::Table(t) => t,
+                _ => {
+                    return Err(errors::Error::InvalidDefinitions(format!(
+                        "cannot set '{key}' since '{part}' is not a table"
+                    )));
+                }
+            };
+        }
+
+        // Values are parsed as TOML literals, like numbers, booleans and
+        // arrays, unless they replace a string or are not valid literals.
+        let literal = toml::from_str::<toml::Table>(&format!("value = {value}"))
+            .ok()
+            .and_then(|mut t| t.remove("value"));
+
+        let value = match (current.get(*last), literal) {
+            (Some(toml::Value::String(_)), _) | (_, None) => {
+                toml::Value::String(value.to_string())
+            }
+            (_, Some(literal)) => literal,
+        };
+
+        current.insert(last.to_string(), value);
+        Ok(())
+    }
+
+    // Returns the definitions, as they were loaded and merged, in the JSON
+    // format. Environment variables references are kept as they were written,
+    // since their values may be secrets.
+    pub(crate) fn to_json(&self) -> serde_json::Value {
+        self.uninterpolated
+            .as_ref()
+            .or(self.source.as_ref())
+            .and_then(|table| serde_json::to_value(table).ok())
+            .unwrap_or_default()
+    }
+
+    // Replaces environment variables references, like '${VAR}' and
+    // '${VAR:-default}', inside every string value of the definitions. It
+    // must be called after the deployment overlay is applied, since sections
+    // of other deployments are discarded.
+    pub(crate) fn interpolate(
+        self,
+        source: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Self, errors::Error> {
+        let Some(mut table) = self.source.clone() else {
+            return Ok(self);
+        };
+
+        table.remove("deploy");
+        let uninterpolated = table.clone();
+        interpolation::interpolate_table(&mut table, "", source)?;
+
+        let mut info = Self::from_table(table)?;
+        info.uninterpolated = Some(uninterpolated);
+        Ok(info)
+    }
+
+    fn parse_layers<P: AsRef<Path>>(paths: &[P]) -> Result<Self, errors::Error> {
+        let mut merged = toml::Table::new();
+
+        for path in paths {
+            let content = std::fs::read_to_string(path.as_ref())
+                .map_err(|e| errors::Error::CouldNotLoadFile(e.to_string()))?;
+
+            let layer: toml::Table = toml::from_str(&content)
+                .map_err(|e| errors::Error::MalformedToml(e.to_string()))?;
+
+            Self::merge_layer(&mut merged, layer);
+        }
+
+        Self::from_table(merged)
+    }
+
+    fn merge_layer(base: &mut toml::Table, layer: toml::Table) {
+        for (key, value) in layer {
+            match (base.get_mut(&key), value) {
+                (Some(toml::Value::Table(current)), toml::Value::Table(table)) => {
+                    Self::merge_layer(current, table);
+                }
+                (_, value) => {
+                    base.insert(key, value);
+                }
+            }
+        }
     }
 
     fn load_service_file(filename: Option<&str>) -> Result<String, errors::Error> {
@@ -156,7 +408,10 @@ impl Definitions {
         }
     }
 
-    fn validate(&self, custom_info: Option<CustomServiceInfo>) -> Result<(), errors::Error> {
+    pub(crate) fn validate(
+        &self,
+        custom_info: Option<CustomServiceInfo>,
+    ) -> Result<(), errors::Error> {
         let context = custom_info.unwrap_or_else(|| {
             let c: CustomServiceInfo = CustomServiceInfo::default();
             c
@@ -166,10 +421,10 @@ impl Definitions {
             Err(e) => Err(errors::Error::InvalidDefinitions(e.to_string())),
             Ok(()) => {
                 if self.types.is_empty() {
-                    Err(errors::Error::EmptyServiceType)
-                } else {
-                    Ok(())
+                    return Err(errors::Error::EmptyServiceType);
                 }
+
+                Ok(())
             }
         }
     }
@@ -196,6 +451,21 @@ impl Definitions {
         }
     }
 
+    pub(crate) fn shutdown(&self) -> Shutdown {
+        match &self.shutdown {
+            None => Shutdown::default(),
+            Some(shutdown) => {
+                let mut shutdown = shutdown.clone();
+                shutdown.merge(Shutdown::default());
+                shutdown
+            }
+        }
+    }
+
+    pub(crate) fn secrets(&self) -> Secrets {
+        self.secrets.clone().unwrap_or_default()
+    }
+
     /// Loads definitions from a feature.
     pub fn load_feature<T>(&self, feature: &str) -> Option<T>
     where
@@ -204,7 +474,7 @@ impl Definitions {
         Self::decode(self.feature(feature))
     }
 
-    fn feature(&self, feature: &str) -> Option<serde_json::Value> {
+    pub(crate) fn feature(&self, feature: &str) -> Option<serde_json::Value> {
         match &self.features {
             None => None,
             Some(features) => features.get(feature).cloned(),
@@ -249,6 +519,142 @@ impl Definitions {
             Some(settings) => serde_json::from_value::<T>(settings.clone()).ok()
         }
     }
+
+    /// Loads definitions from a feature, like `load_feature`, but reporting
+    /// the exact path of invalid settings. It returns `Ok(None)` when there
+    /// are no settings for the feature.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the feature settings could not be decoded.
+    #[allow(clippy::result_large_err)]
+    pub fn try_load_feature<T>(&self, feature: &str) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        Self::try_decode(&format!("features.{feature}"), self.feature(feature))
+    }
+
+    /// Same as `try_load_feature`, but also validates the decoded settings.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the feature settings could not be decoded or
+    /// are not valid.
+    #[allow(clippy::result_large_err)]
+    pub fn try_load_feature_validated<T>(&self, feature: &str) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned + Validate,
+    {
+        let section = format!("features.{feature}");
+        Self::validate_section(&section, Self::try_decode(&section, self.feature(feature))?)
+    }
+
+    /// Loads definitions from a service type, like `load_service`, but
+    /// reporting the exact path of invalid settings. It returns `Ok(None)`
+    /// when there are no settings for the service type.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the service settings could not be decoded.
+    #[allow(clippy::result_large_err)]
+    pub fn try_load_service<T>(&self, service_kind: ServiceKind) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        Self::try_decode(&format!("services.{service_kind}"), self.service(&service_kind))
+    }
+
+    /// Same as `try_load_service`, but also validates the decoded settings.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the service settings could not be decoded or
+    /// are not valid.
+    #[allow(clippy::result_large_err)]
+    pub fn try_load_service_validated<T>(
+        &self,
+        service_kind: ServiceKind,
+    ) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned + Validate,
+    {
+        let section = format!("services.{service_kind}");
+        let settings = Self::try_decode(&section, self.service(&service_kind))?;
+        Self::validate_section(&section, settings)
+    }
+
+    /// Loads the service custom settings, like `custom_settings`, but
+    /// reporting the exact path of invalid settings. It returns `Ok(None)`
+    /// when there is no `[service]` section.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the custom settings could not be decoded.
+    #[allow(clippy::result_large_err)]
+    pub fn try_custom_settings<T>(&self) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        Self::try_decode("service", self.service.clone())
+    }
+
+    /// Same as `try_custom_settings`, but also validates the decoded settings.
+    ///
+    /// # Errors
+    ///
+    /// It returns an `Err` if the custom settings could not be decoded or are
+    /// not valid.
+    #[allow(clippy::result_large_err)]
+    pub fn try_custom_settings_validated<T>(&self) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned + Validate,
+    {
+        Self::validate_section("service", Self::try_decode("service", self.service.clone())?)
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn try_decode<T>(
+        section: &str,
+        data: Option<serde_json::Value>,
+    ) -> crate::errors::Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let Some(data) = data else {
+            return Ok(None);
+        };
+
+        serde_path_to_error::deserialize(data).map(Some).map_err(|e| {
+            let path = match e.path().to_string().as_str() {
+                "." => section.to_string(),
+                path => format!("{section}.{path}"),
+            };
+
+            let error = errors::Error::InvalidSettings(path, e.into_inner().to_string());
+            Self::into_service_error(error)
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn validate_section<T>(section: &str, settings: Option<T>) -> crate::errors::Result<Option<T>>
+    where
+        T: Validate,
+    {
+        if let Some(s) = &settings {
+            s.validate().map_err(|e| {
+                let error = errors::Error::InvalidSettings(section.to_string(), e.to_string());
+                Self::into_service_error(error)
+            })?;
+        }
+
+        Ok(settings)
+    }
+
+    fn into_service_error(e: errors::Error) -> crate::errors::ServiceError {
+        let error: crate::errors::Error = e.into();
+        error.into()
+    }
 }
 
 #[cfg(test)]
@@ -256,6 +662,7 @@ mod tests {
     use super::*;
     use mikros_tests::common::assets_path;
     use serde_derive::Deserialize;
+    use std::sync::Arc;
 
     #[test]
     fn test_load_service_file_with_invalid_settings() {
@@ -271,6 +678,27 @@ mod tests {
         assert!(defs.is_err());
     }
 
+    #[test]
+    fn test_validate_log_level() {
+        let content = r#"
+            name = "my-service"
+            types = ["grpc"]
+            version = "v1.0.0"
+            language = "rust"
+            product = "incredible-product"
+
+            [log]
+            level = "LEVEL"
+            "#;
+
+        let defs = Definitions::from_toml(&content.replace("LEVEL", "warning")).unwrap();
+        assert!(defs.validate(None).is_ok());
+
+        // Unknown levels are not rejected, the service falls back to info.
+        let defs = Definitions::from_toml(&content.replace("LEVEL", "verbose")).unwrap();
+        assert!(defs.validate(None).is_ok());
+    }
+
     #[test]
     fn test_load_service_file_ok() {
         let filename = assets_path().join("definitions/service.toml.ok");
@@ -280,6 +708,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test_load_service_file_ok_hybrid() {
         let filename = assets_path().join("definitions/service.toml.ok_hybrid");
         let defs = Definitions::new(filename.to_str(), None);
@@ -307,6 +736,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_load_features_settings() {
         let filename = assets_path().join("definitions/service.toml.ok");
         let defs = Definitions::new(filename.to_str(), None);
@@ -343,6 +773,59 @@ mod tests {
         assert_eq!(another_api.host, "localhost");
     }
 
+    #[test]
+    fn test_try_load_features_settings() {
+        let filename = assets_path().join("definitions/service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+
+        #[derive(Deserialize, Validate)]
+        struct SimpleApi {
+            enabled: bool,
+            #[validate(length(min = 3))]
+            collections: Vec<String>,
+        }
+
+        let s: Option<SimpleApi> = defs.try_load_feature("simple_api").unwrap();
+        assert!(s.unwrap().enabled);
+
+        let s: Option<SimpleApi> = defs.try_load_feature("unknown_api").unwrap();
+        assert!(s.is_none());
+
+        let err = defs
+            .try_load_feature_validated::<SimpleApi>("simple_api")
+            .err()
+            .unwrap();
+
+        assert!(err.to_string().contains("features.simple_api"));
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct InvalidApi {
+            collections: Vec<i32>,
+        }
+
+        let err = defs
+            .try_load_feature::<InvalidApi>("simple_api")
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("invalid settings at 'features.simple_api.collections[0]'")
+        );
+
+        #[derive(Deserialize, Debug)]
+        #[allow(dead_code)]
+        struct AnotherApi {
+            hostname: String,
+        }
+
+        let err = defs
+            .try_load_feature::<AnotherApi>("another_api")
+            .unwrap_err();
+
+        assert!(err.to_string().contains("missing field `hostname`"));
+    }
+
     #[test]
     fn test_load_service_settings() {
         let custom_info = CustomServiceInfo {
@@ -395,6 +878,172 @@ mod tests {
         assert_eq!(address.unwrap().port, 7071);
     }
 
+    #[test]
+    fn test_load_shutdown_settings() {
+        let filename = assets_path().join("definitions/service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        assert_eq!(defs.shutdown().drain_timeout, Some(30));
+
+        let filename = assets_path().join("definitions/service.toml.ok_shutdown");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        assert_eq!(
+            defs.shutdown().drain_timeout(),
+            std::time::Duration::from_secs(5)
+        );
+    }
+
+    #[test]
+    fn test_load_layered_files() {
+        let base = assets_path().join("definitions/service.toml.ok");
+        let overlay = assets_path().join("definitions/service.toml.ok_overlay");
+        let defs = Definitions::from_files(&[base, overlay]);
+        assert!(defs.is_ok());
+
+        let defs = defs.unwrap();
+        assert_eq!(defs.version, "v1.1.0");
+        assert_eq!(defs.product, "incredible-product");
+        assert_eq!(defs.types.len(), 2);
+
+        #[derive(Deserialize)]
+        struct AnotherApi {
+            enabled: bool,
+            use_tls: bool,
+            host: String,
+        }
+
+        let another_api: AnotherApi = defs.load_feature("another_api").unwrap();
+        assert!(another_api.enabled);
+        assert!(!another_api.use_tls);
+        assert_eq!(another_api.host, "another-api.internal");
+    }
+
+    #[test]
+    fn test_load_deployment_overlay() {
+        let filename = assets_path().join("definitions/service.toml.ok_deploy");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        assert_eq!(defs.log().level, Some("debug".to_string()));
+        assert_eq!(defs.client("user").unwrap().host, "localhost");
+
+        #[derive(Deserialize)]
+        struct SimpleApi {
+            enabled: bool,
+            collections: Vec<String>,
+        }
+
+        let defs = Arc::into_inner(defs)
+            .unwrap()
+            .with_deployment("production")
+            .unwrap();
+
+        assert!(defs.validate(None).is_ok());
+        assert_eq!(defs.log().level, Some("warning".to_string()));
+        assert_eq!(defs.log().display_errors, Some(false));
+        assert_eq!(defs.client("user").unwrap().host, "user.production.svc");
+        assert_eq!(defs.client("user").unwrap().port, 7070);
+
+        let simple_api: SimpleApi = defs.load_feature("simple_api").unwrap();
+        assert!(!simple_api.enabled);
+        assert_eq!(simple_api.collections.len(), 2);
+
+        // Unknown deployments keep the base definitions.
+        let defs = defs.with_deployment("staging").unwrap();
+        assert_eq!(defs.log().level, Some("warning".to_string()));
+    }
+
+    #[test]
+    fn test_interpolate_definitions() {
+        let content = r#"
+            name = "my-service"
+            types = ["grpc"]
+            version = "v1.0.0"
+            language = "rust"
+            product = "incredible-product"
+
+            [clients.user]
+            host = "${USER_HOST:-localhost}"
+            port = 7070
+
+            [deploy.production.clients.user]
+            host = "${USER_HOST}"
+            "#;
+
+        // Sections from other deployments are not interpolated.
+        let defs = Definitions::from_toml(content)
+            .unwrap()
+            .interpolate(&|_| None)
+            .unwrap();
+
+        assert_eq!(defs.client("user").unwrap().host, "localhost");
+
+        let defs = Definitions::from_toml(content)
+            .unwrap()
+            .with_deployment("production")
+            .unwrap();
+
+        let source = |name: &str| (name == "USER_HOST").then(|| "10.0.0.1".to_string());
+        assert_eq!(
+            defs.interpolate(&source).unwrap().client("user").unwrap().host,
+            "10.0.0.1"
+        );
+
+        let defs = Definitions::from_toml(content)
+            .unwrap()
+            .with_deployment("production")
+            .unwrap();
+
+        let err = defs.interpolate(&|_| None).unwrap_err();
+        assert!(err.description().contains("clients.user.host"));
+    }
+
+    #[test]
+    fn test_load_with_overrides() {
+        let filename = assets_path().join("definitions/service.toml.ok_deploy");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        let overrides = vec![
+            ("log.level".to_string(), "error".to_string()),
+            ("version".to_string(), "2.0".to_string()),
+            ("features.simple_api.enabled".to_string(), "false".to_string()),
+            ("shutdown.drain_timeout".to_string(), "10".to_string()),
+        ];
+
+        let defs = Arc::into_inner(defs)
+            .unwrap()
+            .with_overrides(&overrides)
+            .unwrap();
+
+        assert_eq!(defs.log().level, Some("error".to_string()));
+        assert_eq!(defs.version, "2.0");
+        assert_eq!(defs.shutdown().drain_timeout, Some(10));
+        assert_eq!(defs.to_json()["features"]["simple_api"]["enabled"], false);
+
+        let overrides = vec![("log.level.name".to_string(), "debug".to_string())];
+        assert!(defs.with_overrides(&overrides).is_err());
+    }
+
+    #[test]
+    fn test_load_layered_files_with_missing_file() {
+        let base = assets_path().join("definitions/service.toml.ok");
+        let overlay = assets_path().join("definitions/service.toml.missing");
+        assert!(Definitions::from_files(&[base, overlay]).is_err());
+    }
+
+    #[test]
+    fn test_load_from_toml() {
+        let defs = Definitions::from_toml(
+            r#"
+            name = "my-service"
+            types = ["http"]
+            version = "v1.0.0"
+            language = "rust"
+            product = "incredible-product"
+            "#,
+        );
+
+        assert!(defs.is_ok());
+        assert!(defs.unwrap().validate(None).is_ok());
+        assert!(Definitions::from_toml("name = \"my-service\"").is_err());
+    }
+
     #[test]
     fn test_load_service_custom_settings() {
         let filename = assets_path().join("definitions/service.toml.ok_custom_settings");
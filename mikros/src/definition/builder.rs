@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::definition::name::ServiceName;
+use crate::definition::service::{Address, Service};
+use crate::definition::{Client, Definitions, Log, Secrets, ServiceKind, Shutdown};
+
+/// The builder API to create service definitions programmatically, without
+/// any 'service.toml' file.
+pub struct DefinitionsBuilder {
+    definitions: Definitions,
+}
+
+impl DefinitionsBuilder {
+    /// Starts the definitions of a rust service with its mandatory information.
+    pub fn new(name: &str, version: &str, product: &str) -> Self {
+        Self {
+            definitions: Definitions {
+                name: ServiceName::new(name),
+                version: version.to_string(),
+                language: "rust".to_string(),
+                product: product.to_string(),
+                envs: None,
+                log: None,
+                shutdown: None,
+                secrets: None,
+                features: None,
+                services: None,
+                clients: None,
+                service: None,
+                types: Vec::new(),
+                source: None,
+                uninterpolated: None,
+            },
+        }
+    }
+
+    /// Adds a service type using its default port.
+    pub fn with_type(mut self, kind: ServiceKind) -> Self {
+        self.definitions.types.push(Service(kind, None));
+        self
+    }
+
+    /// Adds a service type listening at a specific address.
+    pub fn with_type_address(mut self, kind: ServiceKind, address: Address) -> Self {
+        self.definitions.types.push(Service(kind, Some(address)));
+        self
+    }
+
+    /// Sets the environment variables that the service requires.
+    pub fn with_envs(mut self, envs: &[&str]) -> Self {
+        self.definitions.envs = Some(envs.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    /// Sets the service logging settings, like the `[log]` section.
+    pub fn with_log(mut self, log: Log) -> Self {
+        self.definitions.log = Some(log);
+        self
+    }
+
+    /// Sets how the service finishes, like the `[shutdown]` section.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.definitions.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Sets the secrets providers settings, like the `[secrets]` section.
+    pub fn with_secrets(mut self, secrets: Secrets) -> Self {
+        self.definitions.secrets = Some(secrets);
+        self
+    }
+
+    /// Sets the settings of a feature, like the `[features.<name>]` section.
+    pub fn with_feature(mut self, name: &str, settings: serde_json::Value) -> Self {
+        self.definitions
+            .features
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), settings);
+
+        self
+    }
+
+    /// Sets the settings of a service type, like the `[services.<kind>]`
+    /// section.
+    pub fn with_service_settings(
+        mut self,
+        kind: &ServiceKind,
+        settings: serde_json::Value,
+    ) -> Self {
+        self.definitions
+            .services
+            .get_or_insert_with(HashMap::new)
+            .insert(kind.to_string(), settings);
+
+        self
+    }
+
+    /// Sets the connection settings of a dependent service, like the
+    /// `[clients.<name>]` section.
+    pub fn with_client(mut self, name: &str, client: Client) -> Self {
+        self.definitions
+            .clients
+            .get_or_insert_with(HashMap::new)
+            .insert(name.to_string(), client);
+
+        self
+    }
+
+    /// Sets the service custom settings, like the `[service]` section.
+    pub fn with_custom_settings(mut self, settings: serde_json::Value) -> Self {
+        self.definitions.service = Some(settings);
+        self
+    }
+
+    /// Returns the definitions. They are only validated when the service is
+    /// built with them, using the `ServiceBuilder::with_definitions` API.
+    pub fn build(self) -> Definitions {
+        self.definitions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::Deserialize;
+
+    #[test]
+    fn test_build_definitions() {
+        let defs = DefinitionsBuilder::new("my-service", "v1.0.0", "my-product")
+            .with_type(ServiceKind::Grpc)
+            .with_type_address(ServiceKind::Http, Address::Port(0))
+            .with_feature("simple_api", serde_json::json!({ "enabled": true }))
+            .with_client(
+                "user",
+                Client {
+                    host: "localhost".to_string(),
+                    port: 7070,
+                },
+            )
+            .build();
+
+        assert!(defs.validate(None).is_ok());
+        assert_eq!(defs.types.len(), 2);
+        assert_eq!(defs.client("user").unwrap().port, 7070);
+
+        #[derive(Deserialize)]
+        struct SimpleApi {
+            enabled: bool,
+        }
+
+        let feature: SimpleApi = defs.load_feature("simple_api").unwrap();
+        assert!(feature.enabled);
+    }
+
+    #[test]
+    fn test_build_definitions_without_types() {
+        let defs = DefinitionsBuilder::new("my-service", "v1.0.0", "my-product").build();
+        assert!(defs.validate(None).is_err());
+    }
+}
@@ -6,6 +6,7 @@ crate::module_errors!(
         CouldNotLoadFile(e: String) => "could not load definitions file: {}",
         MalformedToml(e: String) => "malformed toml definitions: {}",
         ServiceNotFound(s: String) => "service definitions not found: {}",
+        InvalidSettings(p: String, e: String) => "invalid settings at '{}': {}",
         EmptyServiceType => "no service type was defined for service"
     }
 );
@@ -0,0 +1,151 @@
+use crate::definition::errors;
+
+// Replaces every '${VAR}' and '${VAR:-default}' occurrence inside string
+// values of the table, recursively, using source to retrieve variables.
+pub(crate) fn interpolate_table(
+    table: &mut toml::Table,
+    path: &str,
+    source: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), errors::Error> {
+    for (key, value) in table.iter_mut() {
+        let path = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{path}.{key}")
+        };
+
+        interpolate_value(value, &path, source)?;
+    }
+
+    Ok(())
+}
+
+fn interpolate_value(
+    value: &mut toml::Value,
+    path: &str,
+    source: &dyn Fn(&str) -> Option<String>,
+) -> Result<(), errors::Error> {
+    match value {
+        toml::Value::String(s) => *s = interpolate(s, path, source)?,
+        toml::Value::Table(table) => interpolate_table(table, path, source)?,
+        toml::Value::Array(values) => {
+            for (i, value) in values.iter_mut().enumerate() {
+                interpolate_value(value, &format!("{path}[{i}]"), source)?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn interpolate(
+    content: &str,
+    path: &str,
+    source: &dyn Fn(&str) -> Option<String>,
+) -> Result<String, errors::Error> {
+    let mut result = String::new();
+    let mut remaining = content;
+
+    while let Some(start) = remaining.find("${") {
+        result.push_str(&remaining[..start]);
+
+        let Some(end) = remaining[start..].find('}') else {
+            return Err(errors::Error::InvalidDefinitions(format!(
+                "{path}: unterminated variable reference"
+            )));
+        };
+
+        let expression = &remaining[start + 2..start + end];
+        let (name, default) = match expression.split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (expression, None),
+        };
+
+        if name.is_empty() {
+            return Err(errors::Error::InvalidDefinitions(format!(
+                "{path}: empty variable reference"
+            )));
+        }
+
+        // Like shells, the default value is also used for empty variables.
+        match (source(name).filter(|v| !v.is_empty()), default) {
+            (Some(value), _) => result.push_str(&value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => {
+                return Err(errors::Error::InvalidDefinitions(format!(
+                    "{path}: environment variable '{name}' is not set"
+                )));
+            }
+        }
+
+        remaining = &remaining[start + end + 1..];
+    }
+
+    result.push_str(remaining);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn source(name: &str) -> Option<String> {
+        HashMap::from([("HOST", "10.0.0.1"), ("EMPTY", "")])
+            .get(name)
+            .map(ToString::to_string)
+    }
+
+    #[test]
+    fn test_interpolate() {
+        assert_eq!(interpolate("${HOST}", "key", &source).unwrap(), "10.0.0.1");
+        assert_eq!(
+            interpolate("http://${HOST}:${PORT:-8080}/api", "key", &source).unwrap(),
+            "http://10.0.0.1:8080/api"
+        );
+        assert_eq!(
+            interpolate("${EMPTY:-default}", "key", &source).unwrap(),
+            "default"
+        );
+        assert_eq!(interpolate("$HOST", "key", &source).unwrap(), "$HOST");
+        assert_eq!(interpolate("${NOT_SET:-}", "key", &source).unwrap(), "");
+    }
+
+    #[test]
+    fn test_interpolate_errors() {
+        let err = interpolate("${NOT_SET}", "clients.user.host", &source).unwrap_err();
+        assert_eq!(
+            err.description(),
+            "invalid service definitions: clients.user.host: environment variable 'NOT_SET' is not set"
+        );
+
+        assert!(interpolate("${HOST", "key", &source).is_err());
+        assert!(interpolate("${}", "key", &source).is_err());
+    }
+
+    #[test]
+    fn test_interpolate_table() {
+        let mut table: toml::Table = toml::from_str(
+            r#"
+            name = "my-service"
+            types = ["http:${PORT:-8080}"]
+
+            [features.simple_api]
+            collections = ["${HOST}", "name"]
+            "#,
+        )
+        .unwrap();
+
+        assert!(interpolate_table(&mut table, "", &source).is_ok());
+        assert_eq!(table["types"][0].as_str(), Some("http:8080"));
+        assert_eq!(
+            table["features"]["simple_api"]["collections"][0].as_str(),
+            Some("10.0.0.1")
+        );
+
+        let mut table: toml::Table = toml::from_str("[service]\nitems = [\"${NOT_SET}\"]").unwrap();
+        let err = interpolate_table(&mut table, "", &source).unwrap_err();
+        assert!(err.description().contains("service.items[0]"));
+    }
+}
@@ -0,0 +1,81 @@
+// Builds the JSON Schema of the core keys of the 'service.toml' file, where
+// features and services sections accept any settings.
+pub(crate) fn core() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "mikros service definitions",
+        "type": "object",
+        "required": ["name", "types", "version", "language", "product"],
+        "properties": {
+            "name": { "type": "string" },
+            "types": {
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "string",
+                    "description": "The service kind, optionally followed by ':' and its port or address."
+                }
+            },
+            "version": { "type": "string" },
+            "language": { "type": "string" },
+            "product": { "type": "string" },
+            "envs": {
+                "type": "array",
+                "items": { "type": "string" }
+            },
+            "log": {
+                "type": "object",
+                "properties": {
+                    "level": { "enum": ["debug", "info", "warning", "warn", "error"] },
+                    "local_timestamp": { "type": "boolean" },
+                    "display_errors": { "type": "boolean" }
+                }
+            },
+            "shutdown": {
+                "type": "object",
+                "properties": {
+                    "drain_timeout": { "type": "integer", "minimum": 0 }
+                }
+            },
+            "secrets": {
+                "type": "object",
+                "properties": {
+                    "directory": { "type": "string" },
+                    "providers": {
+                        "type": "array",
+                        "items": { "type": "string" }
+                    }
+                }
+            },
+            "features": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": { "type": "object" }
+            },
+            "services": {
+                "type": "object",
+                "properties": {},
+                "additionalProperties": { "type": "object" }
+            },
+            "clients": {
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "required": ["host", "port"],
+                    "properties": {
+                        "host": { "type": "string" },
+                        "port": { "type": "integer" }
+                    }
+                }
+            },
+            "service": { "type": "object" },
+            "deploy": {
+                "type": "object",
+                "additionalProperties": {
+                    "type": "object",
+                    "description": "Definitions merged over the base ones for a deployment."
+                }
+            }
+        }
+    })
+}
@@ -1,12 +1,89 @@
-use std::fmt::Formatter;
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 use serde::de::{Deserializer, Error as SerdeError, Visitor};
 use serde::{Deserialize, de::IntoDeserializer};
 
 use crate::definition::ServiceKind;
 
+/// A service type declared in the `types` field of the service definitions,
+/// with the address where it listens, if one was set.
 #[derive(Debug, Clone)]
-pub struct Service(pub ServiceKind, pub Option<i32>);
+pub struct Service(pub ServiceKind, pub Option<Address>);
+
+impl Service {
+    /// Returns the declared TCP port of the service type, if it has one.
+    pub fn port(&self) -> Option<i32> {
+        self.1.as_ref().and_then(Address::port).map(i32::from)
+    }
+}
+
+/// The address where a service kind listens for connections.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    /// Only the port, listening on all IPv4 interfaces.
+    Port(u16),
+
+    /// A complete IPv4 or IPv6 socket address, like `127.0.0.1:8080` or
+    /// `[::1]:7070`.
+    Socket(SocketAddr),
+
+    /// A Unix domain socket path, declared as `unix:/run/svc.sock`.
+    Unix(PathBuf),
+}
+
+impl Address {
+    /// Returns the TCP port of the address, if it has one.
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            Address::Port(port) => Some(*port),
+            Address::Socket(addr) => Some(addr.port()),
+            Address::Unix(_) => None,
+        }
+    }
+
+    /// Returns the socket address, if the address is a complete one.
+    pub fn socket_addr(&self) -> Option<SocketAddr> {
+        match self {
+            Address::Socket(addr) => Some(*addr),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Address {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            if path.is_empty() {
+                return Err("empty unix socket path".to_string());
+            }
+
+            return Ok(Address::Unix(PathBuf::from(path)));
+        }
+
+        if let Ok(addr) = s.parse::<SocketAddr>() {
+            return Ok(Address::Socket(addr));
+        }
+
+        s.parse::<u16>()
+            .map(Address::Port)
+            .map_err(|_| format!("invalid address '{s}'"))
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Address::Port(port) => write!(f, "0.0.0.0:{port}"),
+            Address::Socket(addr) => write!(f, "{addr}"),
+            Address::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
 
 impl<'a> Deserialize<'a> for Service {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -19,33 +96,29 @@ impl<'a> Deserialize<'a> for Service {
             type Value = Service;
 
             fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-                formatter.write_str("a service type in the format `name` or `name:port`")
+                formatter.write_str("a service type in the format `name` or `name:address`")
             }
 
             fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
             where
                 E: SerdeError,
             {
-                let parts: Vec<&str> = v.split(':').collect();
-                let mut port: Option<i32> = None;
-
-                if parts.len() > 1 {
-                    port = Some(
-                        parts[1]
-                            .parse::<i32>()
-                            .map_err(|_| E::custom("invalid port number"))?,
-                    );
-                }
-
-                let service_kind = match parts[0] {
+                let (name, address) = match v.split_once(':') {
+                    None => (v, None),
+                    Some((name, address)) => {
+                        (name, Some(address.parse::<Address>().map_err(E::custom)?))
+                    }
+                };
+
+                let service_kind = match name {
                     "grpc" => ServiceKind::Grpc,
                     "http" => ServiceKind::Http,
                     "native" => ServiceKind::Native,
                     "script" => ServiceKind::Script,
-                    _ => ServiceKind::Custom(parts[0].to_string()),
+                    _ => ServiceKind::Custom(name.to_string()),
                 };
 
-                Ok(Service(service_kind, port))
+                Ok(Service(service_kind, address))
             }
         }
 
@@ -82,6 +155,43 @@ mod tests {
             "types = [\"grpc:9090\", \"http:8080\", \"native\", \"subscriber\", \"consumer:7070\"]";
         let config: Config = toml::from_str(data).unwrap();
         assert_eq!(config.types.len(), 5);
+        assert_eq!(config.types[0].1, Some(Address::Port(9090)));
+        assert_eq!(config.types[2].1, None);
+    }
+
+    #[test]
+    fn test_service_type_with_address() {
+        let data =
+            "types = [\"http:127.0.0.1:8080\", \"grpc:[::1]:7070\", \"http:unix:/run/svc.sock\"]";
+        let config: Config = toml::from_str(data).unwrap();
+
+        assert_eq!(
+            config.types[0].1,
+            Some(Address::Socket("127.0.0.1:8080".parse().unwrap()))
+        );
+        assert_eq!(
+            config.types[1].1,
+            Some(Address::Socket("[::1]:7070".parse().unwrap()))
+        );
+        assert_eq!(
+            config.types[2].1,
+            Some(Address::Unix(PathBuf::from("/run/svc.sock")))
+        );
+        assert_eq!(
+            config.types[1].1.as_ref().and_then(Address::port),
+            Some(7070)
+        );
+    }
+
+    #[test]
+    fn test_service_type_with_invalid_address() {
+        for data in [
+            "types = [\"http:localhost:8080\"]",
+            "types = [\"http:unix:\"]",
+            "types = [\"grpc:70000\"]",
+        ] {
+            assert!(toml::from_str::<Config>(data).is_err());
+        }
     }
 
     #[test]
@@ -2,6 +2,7 @@
 crate::module_errors!(
     Error {
 //        SettingsError(e: String) => "{}",
-        VariableNotSet(v: String) => "'{}' is not set"
+        VariableNotSet(v: String) => "'{}' is not set",
+        InvalidVariables(e: String) => "invalid environment variables: {}"
     }
 );
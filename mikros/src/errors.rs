@@ -1,4 +1,6 @@
+mod catalog;
 mod macros;
+mod status;
 
 use std::fmt::Formatter;
 use std::sync::Arc;
@@ -10,6 +12,8 @@ use serde_derive::{Deserialize, Serialize};
 use crate::logger::Logger;
 use crate::service::context::Context;
 
+pub use catalog::{ErrorCatalog, ErrorDefinition};
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub(crate) enum Error {
     Internal(String),
@@ -19,6 +23,11 @@ pub(crate) enum Error {
     Rpc(String),
     Custom(String),
     PermissionDenied,
+    Unauthenticated,
+    AlreadyExists,
+    RateLimited,
+    Unavailable(String),
+    Timeout(String),
 }
 
 impl Error {
@@ -29,8 +38,13 @@ impl Error {
             Error::PreconditionFailed(msg)
             | Error::Rpc(msg)
             | Error::Internal(msg)
-            | Error::Custom(msg) => msg.to_string(),
+            | Error::Custom(msg)
+            | Error::Unavailable(msg)
+            | Error::Timeout(msg) => msg.to_string(),
             Error::PermissionDenied => "no permission to access the service".to_string(),
+            Error::Unauthenticated => "authentication required".to_string(),
+            Error::AlreadyExists => "already exists".to_string(),
+            Error::RateLimited => "too many requests".to_string(),
         }
     }
 
@@ -43,10 +57,22 @@ impl Error {
             Error::Rpc(_) => "RPCError".to_string(),
             Error::Custom(_) => "CustomError".to_string(),
             Error::PermissionDenied => "PermissionError".to_string(),
+            Error::Unauthenticated => "AuthenticationError".to_string(),
+            Error::AlreadyExists => "AlreadyExistsError".to_string(),
+            Error::RateLimited => "RateLimitError".to_string(),
+            Error::Unavailable(_) => "UnavailableError".to_string(),
+            Error::Timeout(_) => "TimeoutError".to_string(),
         }
     }
 }
 
+// The binary metadata key where older mikros services sent the serialized
+// ServiceError inside gRPC statuses.
+const LEGACY_GRPC_METADATA_KEY: &str = "mikros-error-bin";
+
+// The metadata key with the retry hint of gRPC errors, in seconds.
+const RETRY_AFTER_METADATA_KEY: &str = "retry-after";
+
 // Library Result that should be used by public APIs to keep the standard error
 // across all library and applications code.
 pub type Result<T> = std::result::Result<T, ServiceError>;
@@ -73,6 +99,16 @@ pub struct ServiceError {
     #[serde(skip_serializing_if = "Option::is_none")]
     destination: Option<String>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
+
+    // How many seconds the client should wait before retrying.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after: Option<u64>,
+
+    #[serde(skip)]
+    http_status: Option<StatusCode>,
+
     #[serde(skip)]
     logger: Option<Arc<Logger>>,
 
@@ -89,6 +125,9 @@ impl ServiceError {
             service_name: Some(ctx.service_name()),
             attributes: None,
             destination: None,
+            details: None,
+            retry_after: None,
+            http_status: None,
             logger: Self::get_logger(&ctx),
             concealable_attributes: ctx.envs.response_fields(),
         }
@@ -117,9 +156,70 @@ impl ServiceError {
     }
 
     /// Sets that the current error is related to an argument that didn't
-    /// follow validation rules.
-    pub fn invalid_arguments(ctx: Arc<Context>, _details: serde_json::Value) -> Self {
-        Self::new(ctx, Error::InvalidArguments)
+    /// follow validation rules. The `details` describe which arguments are
+    /// invalid and are sent to the client.
+    pub fn invalid_arguments(ctx: Arc<Context>, details: serde_json::Value) -> Self {
+        let mut error = Self::new(ctx, Error::InvalidArguments);
+
+        error.details = (!details.is_null()).then_some(details);
+        error
+    }
+
+    /// Sets that the current error is related to arguments that failed their
+    /// validation rules, listing each invalid field, its failed rule and its
+    /// message as the error details.
+    pub fn from_validation_errors(ctx: Arc<Context>, errors: &validator::ValidationErrors) -> Self {
+        Self::invalid_arguments(ctx, Self::validation_details(errors))
+    }
+
+    // Builds the details of validation errors, with nested fields named by
+    // their complete path, like 'address.street' or 'items[0].name'.
+    fn validation_details(errors: &validator::ValidationErrors) -> serde_json::Value {
+        fn collect(
+            prefix: &str,
+            errors: &validator::ValidationErrors,
+            details: &mut Vec<serde_json::Value>,
+        ) {
+            let mut fields: Vec<_> = errors.errors().iter().collect();
+            fields.sort_by(|a, b| a.0.cmp(b.0));
+
+            for (name, kind) in fields {
+                let field = if prefix.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{prefix}.{name}")
+                };
+
+                match kind {
+                    validator::ValidationErrorsKind::Field(errors) => {
+                        for e in errors {
+                            let message = match &e.message {
+                                Some(message) => message.to_string(),
+                                None => e.to_string(),
+                            };
+
+                            details.push(serde_json::json!({
+                                "field": field,
+                                "rule": e.code,
+                                "message": message,
+                            }));
+                        }
+                    }
+                    validator::ValidationErrorsKind::Struct(errors) => {
+                        collect(&field, errors, details)
+                    }
+                    validator::ValidationErrorsKind::List(items) => {
+                        for (index, errors) in items {
+                            collect(&format!("{field}[{index}]"), errors, details);
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut details = Vec::new();
+        collect("", errors, &mut details);
+        serde_json::Value::Array(details)
     }
 
     /// Sets that the current error is related to an internal condition which
@@ -148,12 +248,58 @@ impl ServiceError {
         Self::new(ctx, Error::PermissionDenied)
     }
 
+    /// Sets that the current error is related to a client trying to access
+    /// the service without being authenticated.
+    pub fn unauthenticated(ctx: Arc<Context>) -> Self {
+        Self::new(ctx, Error::Unauthenticated)
+    }
+
+    /// Sets that the current error is related to a client trying to create
+    /// a resource that already exists.
+    pub fn already_exists(ctx: Arc<Context>) -> Self {
+        Self::new(ctx, Error::AlreadyExists)
+    }
+
+    /// Sets that the current error is related to a client exceeding the
+    /// number of requests it is allowed to make.
+    pub fn rate_limited(ctx: Arc<Context>) -> Self {
+        Self::new(ctx, Error::RateLimited)
+    }
+
+    /// Sets that the current error is related to the service, or some of its
+    /// dependencies, being temporarily unable to handle requests.
+    pub fn unavailable(ctx: Arc<Context>, msg: &str) -> Self {
+        Self::new(ctx, Error::Unavailable(msg.to_string()))
+    }
+
+    /// Sets that the current error is related to an operation that did not
+    /// finish in time.
+    pub fn timeout(ctx: Arc<Context>, msg: &str) -> Self {
+        Self::new(ctx, Error::Timeout(msg.to_string()))
+    }
+
     /// Adds a code for the error so the client can map and identify their errors.
     pub fn with_code(mut self, code: i32) -> Self {
         self.code = code;
         self
     }
 
+    /// Adds a hint of how long the client should wait before retrying the
+    /// request. It is sent as the `Retry-After` header in HTTP responses and
+    /// as the `retry-after` metadata in gRPC ones, both in seconds, rounded
+    /// up so that sub-second hints are not sent as zero.
+    pub fn with_retry_after(mut self, retry_after: std::time::Duration) -> Self {
+        let rounding = u64::from(retry_after.subsec_nanos() > 0);
+        self.retry_after = Some(retry_after.as_secs().saturating_add(rounding));
+        self
+    }
+
+    /// Returns how long the client should wait before retrying the request,
+    /// if the error has this hint.
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        self.retry_after.map(std::time::Duration::from_secs)
+    }
+
     /// Adds additional information into the error so they can be displayed for
     /// the client if desired.
     pub fn with_attributes(mut self, attributes: serde_json::Value) -> Self {
@@ -173,6 +319,45 @@ impl ServiceError {
         }
     }
 
+    // Returns the error message, if any, or its kind.
+    pub(crate) fn description(&self) -> String {
+        self.message.clone().unwrap_or_else(|| self.kind.clone())
+    }
+
+    // Maps the error kind into its HTTP status code.
+    fn kind_http_status(kind: &str) -> StatusCode {
+        match kind {
+            "NotFoundError" => StatusCode::NOT_FOUND,
+            "ValidationError" => StatusCode::BAD_REQUEST,
+            "ConditionError" => StatusCode::PRECONDITION_FAILED,
+            "PermissionError" => StatusCode::FORBIDDEN,
+            "AuthenticationError" => StatusCode::UNAUTHORIZED,
+            "AlreadyExistsError" => StatusCode::CONFLICT,
+            "RateLimitError" => StatusCode::TOO_MANY_REQUESTS,
+            "UnavailableError" => StatusCode::SERVICE_UNAVAILABLE,
+            "TimeoutError" => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    // Maps the error kind into its canonical gRPC status code.
+    fn grpc_code(&self) -> tonic::Code {
+        match self.kind.as_str() {
+            "NotFoundError" => tonic::Code::NotFound,
+            "ValidationError" => tonic::Code::InvalidArgument,
+            "PermissionError" => tonic::Code::PermissionDenied,
+            "ConditionError" => tonic::Code::FailedPrecondition,
+            "InternalError" => tonic::Code::Internal,
+            "RPCError" => tonic::Code::Unavailable,
+            "AuthenticationError" => tonic::Code::Unauthenticated,
+            "AlreadyExistsError" => tonic::Code::AlreadyExists,
+            "RateLimitError" => tonic::Code::ResourceExhausted,
+            "UnavailableError" => tonic::Code::Unavailable,
+            "TimeoutError" => tonic::Code::DeadlineExceeded,
+            _ => tonic::Code::Unknown,
+        }
+    }
+
     fn serialize(&self) -> String {
         serde_json::to_string(self).unwrap_or("could not serialize the error message".to_string())
     }
@@ -182,12 +367,27 @@ impl ServiceError {
     // operation inside the tests.
     #[cfg(test)]
     fn hide_field(mut self, field: &str) -> Self {
-        let mut fields = self.concealable_attributes.unwrap_or(Vec::new());
+        let mut fields = self.concealable_attributes.unwrap_or_default();
         fields.push(field.to_string());
         self.concealable_attributes = Some(fields);
         self
     }
 
+    /// Rebuilds an error sent by a mikros service inside a gRPC status. It
+    /// returns `None` if the status was not created from a `ServiceError`.
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        if let Some(error) = status::decode(status.details()) {
+            return Some(error);
+        }
+
+        // Errors from services that still send them inside the binary
+        // metadata, or the message, are also supported.
+        match status.metadata().get_bin(LEGACY_GRPC_METADATA_KEY) {
+            Some(value) => serde_json::from_slice(&value.to_bytes().ok()?).ok(),
+            None => serde_json::from_str(status.message()).ok(),
+        }
+    }
+
     // Translates an Error enum into a ServiceError object.
     pub(crate) fn from_error(ctx: Arc<Context>, error: Error) -> Self {
         Self::new(ctx, error)
@@ -237,33 +437,76 @@ impl From<ServiceError> for tonic::Status {
                 if field == "destination" {
                     error.destination = None;
                 }
+
+                if field == "details" {
+                    error.details = None;
+                }
             }
         }
 
-        // Return our error always as an (gRPC) Unknown?
-        let message = error.serialize();
-        tonic::Status::unknown(message)
+        // The status keeps only the message, so standard clients can handle
+        // it, while the complete error travels as google.rpc.Status details.
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        if let Some(retry_after) = error.retry_after {
+            metadata.insert(RETRY_AFTER_METADATA_KEY, retry_after.into());
+        }
+
+        let code = error.grpc_code();
+        let details = status::encode(code, &error);
+        tonic::Status::with_details_and_metadata(
+            code,
+            error.description(),
+            details.into(),
+            metadata,
+        )
     }
 }
 
 impl From<tonic::Status> for ServiceError {
     fn from(status: tonic::Status) -> Self {
-        let error: ServiceError = serde_json::from_str(status.message()).unwrap();
-        error
+        if let Some(error) = Self::from_status(&status) {
+            return error;
+        }
+
+        // Errors from other gRPC servers, or transport failures, keep their
+        // original code and message.
+        let destination = status
+            .metadata()
+            .get(crate::grpc::DESTINATION_METADATA_KEY)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        let error = Error::Rpc(status.message().to_string());
+        Self {
+            code: status.code() as i32,
+            kind: error.kind(),
+            message: Some(error.description()),
+            service_name: None,
+            attributes: None,
+            destination,
+            details: None,
+            retry_after: None,
+            http_status: None,
+            logger: None,
+            concealable_attributes: None,
+        }
     }
 }
 
 impl IntoResponse for ServiceError {
     fn into_response(self) -> Response {
-        let code = match self.kind.as_str() {
-            "NotFoundError" => StatusCode::NOT_FOUND,
-            "ValidationError" => StatusCode::BAD_REQUEST,
-            "ConditionError" => StatusCode::PRECONDITION_FAILED,
-            "PermissionError" => StatusCode::FORBIDDEN,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        let code = self
+            .http_status
+            .unwrap_or_else(|| Self::kind_http_status(&self.kind));
+
+        let mut response = (code, self.serialize()).into_response();
+        if let Some(retry_after) = self.retry_after {
+            response
+                .headers_mut()
+                .insert(http::header::RETRY_AFTER, retry_after.into());
+        }
 
-        (code, self.serialize()).into_response()
+        response
     }
 }
 
@@ -292,18 +535,33 @@ impl From<Error> for ServiceError {
             service_name: None,
             attributes: None,
             destination: None,
+            details: None,
+            retry_after: None,
+            http_status: None,
             logger: None,
             concealable_attributes: None,
         }
     }
 }
 
+// Allows validation failures to be returned directly by handlers, although
+// without the service information, which requires the Context.
+impl From<validator::ValidationErrors> for ServiceError {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        let mut error: ServiceError = Error::InvalidArguments.into();
+
+        error.details = Some(Self::validation_details(&errors));
+        error
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::definition::Definitions;
     use crate::env::Env;
     use crate::logger::builder::LoggerBuilder;
+    use validator::Validate;
 
     fn assets_path() -> std::path::PathBuf {
         let mut p = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -345,6 +603,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_service_error_without_message_field() {
         let ctx = build_context();
         let error = ServiceError::rpc(ctx.clone(), "http", "connection failed")
@@ -372,6 +631,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_service_error_without_service_name_field() {
         let ctx = build_context();
         let error = ServiceError::rpc(ctx.clone(), "http", "connection failed")
@@ -399,6 +659,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_service_error_without_attributes_field() {
         let ctx = build_context();
         let error = ServiceError::rpc(ctx.clone(), "http", "connection failed")
@@ -420,6 +681,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_service_error_without_destination_field() {
         let ctx = build_context();
         let error = ServiceError::rpc(ctx.clone(), "http", "connection failed")
@@ -447,6 +709,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_service_error_without_all_fields() {
         let ctx = build_context();
         let error = ServiceError::rpc(ctx.clone(), "http", "connection failed")
@@ -502,5 +765,343 @@ mod tests {
         // PermissionDenied
         let permission_denied = ServiceError::permission_denied(ctx.clone());
         assert_eq!(permission_denied.kind, "PermissionError".to_string());
+
+        // Unauthenticated
+        let unauthenticated = ServiceError::unauthenticated(ctx.clone());
+        assert_eq!(unauthenticated.kind, "AuthenticationError".to_string());
+
+        // AlreadyExists
+        let already_exists = ServiceError::already_exists(ctx.clone());
+        assert_eq!(already_exists.kind, "AlreadyExistsError".to_string());
+
+        // RateLimited
+        let rate_limited = ServiceError::rate_limited(ctx.clone());
+        assert_eq!(rate_limited.kind, "RateLimitError".to_string());
+
+        // Unavailable
+        let unavailable = ServiceError::unavailable(ctx.clone(), "database is down");
+        assert_eq!(unavailable.kind, "UnavailableError".to_string());
+
+        // Timeout
+        let timeout = ServiceError::timeout(ctx.clone(), "query took too long");
+        assert_eq!(timeout.kind, "TimeoutError".to_string());
+    }
+
+    #[test]
+    fn test_service_error_grpc_codes() {
+        let ctx = build_context();
+        let errors = vec![
+            (ServiceError::not_found(ctx.clone()), tonic::Code::NotFound),
+            (
+                ServiceError::invalid_arguments(ctx.clone(), serde_json::json!({})),
+                tonic::Code::InvalidArgument,
+            ),
+            (
+                ServiceError::permission_denied(ctx.clone()),
+                tonic::Code::PermissionDenied,
+            ),
+            (
+                ServiceError::precondition_failed(ctx.clone(), "failed"),
+                tonic::Code::FailedPrecondition,
+            ),
+            (
+                ServiceError::internal(ctx.clone(), "failed"),
+                tonic::Code::Internal,
+            ),
+            (
+                ServiceError::rpc(ctx.clone(), "http", "failed"),
+                tonic::Code::Unavailable,
+            ),
+            (
+                ServiceError::custom(ctx.clone(), "failed"),
+                tonic::Code::Unknown,
+            ),
+            (
+                ServiceError::unauthenticated(ctx.clone()),
+                tonic::Code::Unauthenticated,
+            ),
+            (
+                ServiceError::already_exists(ctx.clone()),
+                tonic::Code::AlreadyExists,
+            ),
+            (
+                ServiceError::rate_limited(ctx.clone()),
+                tonic::Code::ResourceExhausted,
+            ),
+            (
+                ServiceError::unavailable(ctx.clone(), "failed"),
+                tonic::Code::Unavailable,
+            ),
+            (
+                ServiceError::timeout(ctx.clone(), "failed"),
+                tonic::Code::DeadlineExceeded,
+            ),
+        ];
+
+        for (error, code) in errors {
+            let status: tonic::Status = error.into();
+            assert_eq!(status.code(), code);
+            assert!(ServiceError::from_status(&status).is_some());
+        }
+    }
+
+    #[test]
+    fn test_service_error_grpc_message() {
+        let ctx = build_context();
+        let status: tonic::Status = ServiceError::precondition_failed(ctx.clone(), "no stock")
+            .with_code(7)
+            .into();
+
+        assert_eq!(status.message(), "no stock");
+
+        let error: ServiceError = status.into();
+        assert_eq!(error.code, 7);
+        assert_eq!(error.kind, "ConditionError");
+        assert_eq!(error.message.unwrap(), "no stock");
+    }
+
+    #[test]
+    fn test_service_error_grpc_status_details() {
+        let ctx = build_context();
+        let status: tonic::Status = ServiceError::not_found(ctx.clone())
+            .with_code(42)
+            .with_attributes(serde_json::json!({ "id": 7, "ratio": 0.5 }))
+            .into();
+
+        // The error must survive the trip through the response trailers.
+        let mut headers = http::HeaderMap::new();
+        status.add_header(&mut headers).unwrap();
+        assert!(headers.contains_key("grpc-status-details-bin"));
+        assert!(!headers.contains_key(LEGACY_GRPC_METADATA_KEY));
+
+        let status = tonic::Status::from_header_map(&headers).unwrap();
+        let error: ServiceError = status.into();
+        assert_eq!(error.code, 42);
+        assert_eq!(error.kind, "NotFoundError");
+        assert_eq!(error.service_name.unwrap(), "my-service");
+        assert_eq!(
+            error.attributes.unwrap(),
+            serde_json::json!({ "id": 7, "ratio": 0.5 })
+        );
+    }
+
+    #[test]
+    fn test_service_error_from_legacy_status() {
+        let mut status = tonic::Status::not_found("not found");
+        status.metadata_mut().insert_bin(
+            LEGACY_GRPC_METADATA_KEY,
+            tonic::metadata::MetadataValue::from_bytes(br#"{"code":3,"kind":"NotFoundError"}"#),
+        );
+
+        let error = ServiceError::from_status(&status).unwrap();
+        assert_eq!(error.code, 3);
+        assert_eq!(error.kind, "NotFoundError");
+    }
+
+    #[test]
+    fn test_service_error_from_foreign_status() {
+        let status = tonic::Status::not_found("user not found");
+        assert!(ServiceError::from_status(&status).is_none());
+
+        let error: ServiceError = status.into();
+        assert_eq!(error.code, tonic::Code::NotFound as i32);
+        assert_eq!(error.kind, "RPCError");
+        assert_eq!(error.message.unwrap(), "user not found");
+        assert!(error.destination.is_none());
+
+        let mut status = tonic::Status::unavailable("connection refused");
+        status.metadata_mut().insert(
+            crate::grpc::DESTINATION_METADATA_KEY,
+            tonic::metadata::MetadataValue::from_static("greeter"),
+        );
+
+        let error: ServiceError = status.into();
+        assert_eq!(error.code, tonic::Code::Unavailable as i32);
+        assert_eq!(error.destination.unwrap(), "greeter");
+    }
+
+    #[derive(Validate)]
+    struct Address {
+        #[validate(length(min = 1, message = "street cannot be empty"))]
+        street: String,
+    }
+
+    #[derive(Validate)]
+    struct User {
+        #[validate(range(min = 18))]
+        age: i32,
+
+        #[validate(email)]
+        email: String,
+
+        #[validate(nested)]
+        address: Address,
+    }
+
+    fn invalid_user() -> validator::ValidationErrors {
+        let user = User {
+            age: 10,
+            email: "john".to_string(),
+            address: Address {
+                street: String::new(),
+            },
+        };
+
+        user.validate().unwrap_err()
+    }
+
+    #[test]
+    fn test_invalid_arguments_details() {
+        let ctx = build_context();
+        let error = ServiceError::from_validation_errors(ctx.clone(), &invalid_user());
+        assert_eq!(error.kind, "ValidationError");
+
+        let details = error.details.clone().unwrap();
+        assert_eq!(details.as_array().unwrap().len(), 3);
+        assert_eq!(
+            details[0],
+            serde_json::json!({
+                "field": "address.street",
+                "rule": "length",
+                "message": "street cannot be empty",
+            })
+        );
+        assert_eq!(details[1]["field"], "age");
+        assert_eq!(details[1]["rule"], "range");
+        assert_eq!(details[2]["field"], "email");
+        assert_eq!(details[2]["rule"], "email");
+
+        // HTTP body
+        let body: serde_json::Value = serde_json::from_str(&error.serialize()).unwrap();
+        assert_eq!(body["details"], details);
+
+        // gRPC
+        let status: tonic::Status = error.into();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        let deserialized: ServiceError = status.into();
+        assert_eq!(deserialized.details.unwrap(), details);
+
+        let error: ServiceError = invalid_user().into();
+        assert_eq!(error.kind, "ValidationError");
+        assert_eq!(error.details.unwrap().as_array().unwrap().len(), 3);
+
+        let error = ServiceError::invalid_arguments(ctx, serde_json::Value::Null);
+        assert!(error.details.is_none());
+    }
+
+    #[test]
+    fn test_service_error_http_codes() {
+        let ctx = build_context();
+        let errors = vec![
+            (
+                ServiceError::unauthenticated(ctx.clone()),
+                StatusCode::UNAUTHORIZED,
+            ),
+            (
+                ServiceError::already_exists(ctx.clone()),
+                StatusCode::CONFLICT,
+            ),
+            (
+                ServiceError::rate_limited(ctx.clone()),
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (
+                ServiceError::unavailable(ctx.clone(), "failed"),
+                StatusCode::SERVICE_UNAVAILABLE,
+            ),
+            (
+                ServiceError::timeout(ctx.clone(), "failed"),
+                StatusCode::GATEWAY_TIMEOUT,
+            ),
+        ];
+
+        for (error, code) in errors {
+            let response = error.into_response();
+            assert_eq!(response.status(), code);
+            assert!(response.headers().get(http::header::RETRY_AFTER).is_none());
+        }
+    }
+
+    #[test]
+    fn test_service_error_retry_after() {
+        let ctx = build_context();
+        let error = ServiceError::rate_limited(ctx.clone())
+            .with_retry_after(std::time::Duration::from_secs(30));
+
+        assert_eq!(
+            error.retry_after(),
+            Some(std::time::Duration::from_secs(30))
+        );
+
+        let response = error.into_response();
+        assert_eq!(response.headers()[http::header::RETRY_AFTER], "30");
+
+        let status: tonic::Status = ServiceError::unavailable(ctx.clone(), "restarting")
+            .with_retry_after(std::time::Duration::from_secs(5))
+            .into();
+
+        assert_eq!(
+            status.metadata().get(RETRY_AFTER_METADATA_KEY).unwrap(),
+            "5"
+        );
+
+        let error: ServiceError = status.into();
+        assert_eq!(error.kind, "UnavailableError");
+        assert_eq!(error.retry_after(), Some(std::time::Duration::from_secs(5)));
+
+        // Sub-second hints are rounded up.
+        let response = ServiceError::rate_limited(ctx.clone())
+            .with_retry_after(std::time::Duration::from_millis(500))
+            .into_response();
+
+        assert_eq!(response.headers()[http::header::RETRY_AFTER], "1");
+    }
+
+    #[derive(mikros_macros::ErrorCatalog)]
+    enum OrderError {
+        #[catalog(code = 1001, kind = "not_found", message = "order {id} not found")]
+        NotFound { id: String },
+
+        #[catalog(code = 1002, message = "payment declined: {0}", http_status = 402)]
+        PaymentDeclined(String),
+    }
+
+    #[test]
+    fn test_error_catalog_into_service_error() {
+        let ctx = build_context();
+        let error = OrderError::NotFound {
+            id: "42".to_string(),
+        }
+        .into_service_error(ctx.clone());
+
+        assert_eq!(error.code, 1001);
+        assert_eq!(error.kind, "NotFoundError");
+        assert_eq!(error.message.as_deref(), Some("order 42 not found"));
+        assert_eq!(error.into_response().status(), StatusCode::NOT_FOUND);
+
+        let error = OrderError::PaymentDeclined("no funds".to_string()).into_service_error(ctx);
+        assert_eq!(error.kind, "CustomError");
+        assert_eq!(error.description(), "payment declined: no funds");
+        assert_eq!(error.into_response().status(), StatusCode::PAYMENT_REQUIRED);
+
+        assert_eq!(
+            OrderError::catalog(),
+            serde_json::json!([
+                {
+                    "name": "NotFound",
+                    "code": 1001,
+                    "kind": "NotFoundError",
+                    "http_status": 404,
+                    "message": "order {id} not found",
+                },
+                {
+                    "name": "PaymentDeclined",
+                    "code": 1002,
+                    "kind": "CustomError",
+                    "http_status": 402,
+                    "message": "payment declined: {0}",
+                },
+            ])
+        );
     }
 }
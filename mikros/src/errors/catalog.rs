@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use http::StatusCode;
+
+use crate::errors::{Error, ServiceError};
+use crate::service::context::Context;
+
+/// The definition of an error from an application catalog.
+#[derive(Clone, Debug)]
+pub struct ErrorDefinition {
+    /// The enum variant name.
+    pub name: &'static str,
+
+    /// The stable code that identifies the error.
+    pub code: i32,
+
+    /// The error kind, named after the `ServiceError` API that creates it,
+    /// like "not_found" or "precondition_failed".
+    pub kind: &'static str,
+
+    /// The HTTP status code used instead of the kind default one.
+    pub http_status: Option<u16>,
+
+    /// The message template, where `{field}` is replaced by the value of the
+    /// variant field.
+    pub message: &'static str,
+}
+
+impl ErrorDefinition {
+    fn error(&self, message: String) -> Error {
+        match self.kind {
+            "internal" => Error::Internal(message),
+            "not_found" => Error::NotFound,
+            "invalid_arguments" => Error::InvalidArguments,
+            "precondition_failed" => Error::PreconditionFailed(message),
+            "permission_denied" => Error::PermissionDenied,
+            "unauthenticated" => Error::Unauthenticated,
+            "already_exists" => Error::AlreadyExists,
+            "rate_limited" => Error::RateLimited,
+            "unavailable" => Error::Unavailable(message),
+            "timeout" => Error::Timeout(message),
+            _ => Error::Custom(message),
+        }
+    }
+
+    fn http_status(&self, kind: &str) -> StatusCode {
+        self.http_status
+            .and_then(|status| StatusCode::from_u16(status).ok())
+            .unwrap_or_else(|| ServiceError::kind_http_status(kind))
+    }
+}
+
+/// ErrorCatalog is implemented by application enums that define their own
+/// domain errors, usually through the `ErrorCatalog` derive macro from
+/// `mikros-macros`:
+///
+/// ```ignore
+/// use mikros::errors::ErrorCatalog;
+///
+/// #[derive(mikros_macros::ErrorCatalog)]
+/// pub enum OrderError {
+///     #[catalog(code = 1001, kind = "not_found", message = "order {id} not found")]
+///     NotFound { id: String },
+///
+///     #[catalog(code = 1002, kind = "already_exists", message = "order {0} already exists")]
+///     AlreadyExists(String),
+///
+///     #[catalog(code = 1003, message = "payment declined")]
+///     PaymentDeclined,
+/// }
+///
+/// fn create(ctx: Arc<Context>) -> mikros::errors::Result<()> {
+///     Err(OrderError::PaymentDeclined.into_service_error(ctx))
+/// }
+/// ```
+pub trait ErrorCatalog: Sized {
+    /// Returns the definitions of every error of the catalog.
+    fn definitions() -> Vec<ErrorDefinition>;
+
+    /// Returns the definition of the error.
+    fn definition(&self) -> ErrorDefinition;
+
+    /// Returns the error message, with the error fields interpolated.
+    fn message(&self) -> String;
+
+    /// Converts the error into a `ServiceError`, with its code, kind, HTTP
+    /// status and message.
+    fn into_service_error(self, ctx: Arc<Context>) -> ServiceError {
+        let definition = self.definition();
+        let message = self.message();
+        let mut error = ServiceError::new(ctx, definition.error(message.clone()));
+
+        error.message = Some(message);
+        error.code = definition.code;
+        error.http_status = Some(definition.http_status(&error.kind));
+        error
+    }
+
+    /// Exports the catalog as JSON, so clients can map the error codes.
+    fn catalog() -> serde_json::Value {
+        Self::definitions()
+            .iter()
+            .map(|definition| {
+                let kind = definition.error(String::new()).kind();
+                serde_json::json!({
+                    "name": definition.name,
+                    "code": definition.code,
+                    "kind": kind,
+                    "http_status": definition.http_status(&kind).as_u16(),
+                    "message": definition.message,
+                })
+            })
+            .collect()
+    }
+}
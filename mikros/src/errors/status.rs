@@ -0,0 +1,203 @@
+use prost::Message;
+use prost_types::value::Kind;
+
+use crate::errors::ServiceError;
+
+// The domain of the ErrorInfo details, where the error kinds, used as their
+// reason, are defined.
+const ERROR_INFO_DOMAIN: &str = "mikros";
+
+const ERROR_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.ErrorInfo";
+const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+const STRUCT_TYPE_URL: &str = "type.googleapis.com/google.protobuf.Struct";
+
+// The google.rpc.Status message, carried by the 'grpc-status-details-bin'
+// trailer of gRPC responses.
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+
+    #[prost(string, tag = "2")]
+    message: String,
+
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<prost_types::Any>,
+}
+
+// The google.rpc.ErrorInfo message.
+#[derive(Clone, PartialEq, Message)]
+struct ErrorInfo {
+    #[prost(string, tag = "1")]
+    reason: String,
+
+    #[prost(string, tag = "2")]
+    domain: String,
+
+    #[prost(map = "string, string", tag = "3")]
+    metadata: std::collections::HashMap<String, String>,
+}
+
+// The google.rpc.RetryInfo message.
+#[derive(Clone, PartialEq, Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<prost_types::Duration>,
+}
+
+// Encodes the error as a google.rpc.Status with its details: an ErrorInfo,
+// with the error kind as reason, a RetryInfo, if the error has a retry hint,
+// and the complete serialized error as a google.protobuf.Struct, which mikros
+// clients use to rebuild it.
+pub(super) fn encode(code: tonic::Code, error: &ServiceError) -> Vec<u8> {
+    let mut details = vec![any(
+        ERROR_INFO_TYPE_URL,
+        &ErrorInfo {
+            reason: error.kind.clone(),
+            domain: ERROR_INFO_DOMAIN.to_string(),
+            metadata: [("code".to_string(), error.code.to_string())].into(),
+        },
+    )];
+
+    if let Some(retry_after) = error.retry_after {
+        let retry_delay = prost_types::Duration {
+            seconds: i64::try_from(retry_after).unwrap_or(i64::MAX),
+            nanos: 0,
+        };
+
+        details.push(any(
+            RETRY_INFO_TYPE_URL,
+            &RetryInfo {
+                retry_delay: Some(retry_delay),
+            },
+        ));
+    }
+
+    if let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(error) {
+        details.push(any(STRUCT_TYPE_URL, &to_struct(fields)));
+    }
+
+    RpcStatus {
+        code: code as i32,
+        message: error.description(),
+        details,
+    }
+    .encode_to_vec()
+}
+
+// Rebuilds the error from an encoded google.rpc.Status, if it carries one.
+pub(super) fn decode(status_details: &[u8]) -> Option<ServiceError> {
+    let status = RpcStatus::decode(status_details).ok()?;
+    let details = status
+        .details
+        .iter()
+        .find(|detail| detail.type_url == STRUCT_TYPE_URL)?;
+
+    let fields = prost_types::Struct::decode(details.value.as_slice()).ok()?;
+    serde_json::from_value(from_struct(fields)).ok()
+}
+
+fn any(type_url: &str, message: &impl Message) -> prost_types::Any {
+    prost_types::Any {
+        type_url: type_url.to_string(),
+        value: message.encode_to_vec(),
+    }
+}
+
+fn to_struct(fields: serde_json::Map<String, serde_json::Value>) -> prost_types::Struct {
+    prost_types::Struct {
+        fields: fields
+            .into_iter()
+            .map(|(name, value)| (name, to_value(value)))
+            .collect(),
+    }
+}
+
+fn to_value(value: serde_json::Value) -> prost_types::Value {
+    let kind = match value {
+        serde_json::Value::Null => Kind::NullValue(0),
+        serde_json::Value::Bool(b) => Kind::BoolValue(b),
+        serde_json::Value::Number(n) => Kind::NumberValue(n.as_f64().unwrap_or_default()),
+        serde_json::Value::String(s) => Kind::StringValue(s),
+        serde_json::Value::Array(values) => Kind::ListValue(prost_types::ListValue {
+            values: values.into_iter().map(to_value).collect(),
+        }),
+        serde_json::Value::Object(fields) => Kind::StructValue(to_struct(fields)),
+    };
+
+    prost_types::Value { kind: Some(kind) }
+}
+
+fn from_struct(fields: prost_types::Struct) -> serde_json::Value {
+    serde_json::Value::Object(
+        fields
+            .fields
+            .into_iter()
+            .map(|(name, value)| (name, from_value(value)))
+            .collect(),
+    )
+}
+
+fn from_value(value: prost_types::Value) -> serde_json::Value {
+    match value.kind {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::BoolValue(b)) => serde_json::Value::Bool(b),
+        Some(Kind::NumberValue(n)) => from_number(n),
+        Some(Kind::StringValue(s)) => serde_json::Value::String(s),
+        Some(Kind::ListValue(list)) => {
+            serde_json::Value::Array(list.values.into_iter().map(from_value).collect())
+        }
+        Some(Kind::StructValue(fields)) => from_struct(fields),
+    }
+}
+
+// Struct numbers are always doubles, so integral values are restored as
+// integers to be deserialized into integer fields, like the error code.
+fn from_number(n: f64) -> serde_json::Value {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+
+    if n.fract() == 0.0 && n.abs() <= MAX_SAFE_INTEGER {
+        serde_json::Value::from(n as i64)
+    } else {
+        serde_json::Number::from_f64(n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_as_standard_status() {
+        let error: ServiceError = serde_json::from_value(serde_json::json!({
+            "code": 12,
+            "kind": "RateLimitError",
+            "message": "too many requests",
+            "retry_after": 30,
+        }))
+        .unwrap();
+
+        // Clients without mikros read it as a plain google.rpc.Status.
+        let status =
+            RpcStatus::decode(encode(tonic::Code::ResourceExhausted, &error).as_slice()).unwrap();
+
+        assert_eq!(status.code, tonic::Code::ResourceExhausted as i32);
+        assert_eq!(status.message, "too many requests");
+        assert_eq!(status.details.len(), 3);
+
+        let info = ErrorInfo::decode(status.details[0].value.as_slice()).unwrap();
+        assert_eq!(status.details[0].type_url, ERROR_INFO_TYPE_URL);
+        assert_eq!(info.reason, "RateLimitError");
+        assert_eq!(info.domain, "mikros");
+        assert_eq!(info.metadata["code"], "12");
+
+        let retry = RetryInfo::decode(status.details[1].value.as_slice()).unwrap();
+        assert_eq!(retry.retry_delay.unwrap().seconds, 30);
+
+        let decoded = decode(&status.encode_to_vec()).unwrap();
+        assert_eq!(decoded.retry_after, Some(30));
+        assert!(decode(b"not a status").is_none());
+    }
+}
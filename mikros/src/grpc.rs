@@ -1,9 +1,22 @@
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
+use tokio_rustls::rustls::pki_types::CertificateDer;
+#[cfg(unix)]
+use tonic::transport::server::UdsConnectInfo;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
 use tower::{Layer, Service};
 
 use crate::service::context;
+use crate::tls::PeerIdentity;
+
+// The metadata key that carries the name of the service a gRPC client is
+// connected to.
+pub(crate) const DESTINATION_METADATA_KEY: &str = "mikros-destination";
+
+/// The channel used by gRPC clients created with the `link_grpc_service!`
+/// macro and its `with_destination` option.
+pub type Channel = DestinationMiddleware<tonic::transport::Channel>;
 
 #[derive(Clone)]
 pub(crate) struct ContextExtractor {
@@ -52,9 +65,221 @@ where
         let mut inner = std::mem::replace(&mut self.inner, clone);
 
         req.extensions_mut().insert(self.ctx.clone());
+
+        // Exposes the client certificates, if any, when running with TLS.
+        if let Some(peer) = peer_certs(&req).and_then(|certs| PeerIdentity::new(certs.to_vec())) {
+            req.extensions_mut().insert(peer);
+        }
+
         Box::pin(async move {
             let response = inner.call(req).await?;
             Ok(response)
         })
     }
 }
+
+// Retrieves the client certificates of a TLS connection, which can be either
+// a TCP or a Unix socket one.
+fn peer_certs<B>(req: &http::Request<B>) -> Option<Arc<Vec<CertificateDer<'static>>>> {
+    let extensions = req.extensions();
+
+    if let Some(info) = extensions.get::<TlsConnectInfo<TcpConnectInfo>>() {
+        return info.peer_certs();
+    }
+
+    #[cfg(unix)]
+    if let Some(info) = extensions.get::<TlsConnectInfo<UdsConnectInfo>>() {
+        return info.peer_certs();
+    }
+
+    None
+}
+
+/// Connects to a gRPC service, identifying it as `destination` in the errors
+/// received through the returned channel.
+pub async fn connect(url: String, destination: &str) -> Result<Channel, tonic::transport::Error> {
+    let channel = tonic::transport::Endpoint::new(url)?.connect().await?;
+    Ok(DestinationMiddleware::new(channel, destination))
+}
+
+/// A client middleware that adds the name of the service being called to
+/// every response and transport failure, so errors received from it can
+/// identify their destination.
+///
+/// The name is added as a response header, so it only reaches statuses of
+/// trailers-only responses, i.e., errors returned by the server before any
+/// message is sent, which tonic builds from these headers. Statuses sent in
+/// the trailers after a response body, like a stream that fails after some
+/// messages, do not have it.
+#[derive(Clone)]
+pub struct DestinationMiddleware<S> {
+    inner: S,
+    destination: Option<http::HeaderValue>,
+}
+
+impl<S> DestinationMiddleware<S> {
+    fn new(inner: S, destination: &str) -> Self {
+        Self {
+            inner,
+            destination: http::HeaderValue::from_str(destination).ok(),
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for DestinationMiddleware<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+    type Future = futures::future::BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, ctx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(ctx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let destination = self.destination.clone();
+        let future = self.inner.call(req);
+
+        Box::pin(async move {
+            match future.await {
+                Ok(mut response) => {
+                    // Statuses sent without a body are built from these
+                    // headers.
+                    if let Some(destination) = destination {
+                        response
+                            .headers_mut()
+                            .insert(DESTINATION_METADATA_KEY, destination);
+                    }
+
+                    Ok(response)
+                }
+                Err(e) => {
+                    let mut status = tonic::Status::from_error(e.into());
+                    if let Some(destination) = destination
+                        .and_then(|d| tonic::metadata::MetadataValue::try_from(d.as_bytes()).ok())
+                    {
+                        status
+                            .metadata_mut()
+                            .insert(DESTINATION_METADATA_KEY, destination);
+                    }
+
+                    Err(Box::new(status) as Self::Error)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone)]
+    struct Unreachable;
+
+    impl Service<http::Request<()>> for Unreachable {
+        type Response = http::Response<()>;
+        type Error = std::io::Error;
+        type Future = futures::future::Ready<Result<Self::Response, Self::Error>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: http::Request<()>) -> Self::Future {
+            futures::future::ready(Err(std::io::Error::other("connection refused")))
+        }
+    }
+
+    #[test]
+    fn test_channel_is_a_grpc_service() {
+        fn assert_grpc_service<T>()
+        where
+            T: tonic::client::GrpcService<tonic::body::BoxBody> + Clone,
+            T::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+        {
+        }
+
+        assert_grpc_service::<Channel>();
+    }
+
+    // A client with the same constructors of the tonic generated ones.
+    struct GreeterClient<T>(#[allow(dead_code)] T);
+
+    impl GreeterClient<tonic::transport::Channel> {
+        async fn connect(url: String) -> Result<Self, tonic::transport::Error> {
+            let channel = tonic::transport::Endpoint::new(url)?.connect().await?;
+            Ok(Self(channel))
+        }
+    }
+
+    impl<T> GreeterClient<T> {
+        fn new(channel: T) -> Self {
+            Self(channel)
+        }
+    }
+
+    fn build_context() -> Arc<context::Context> {
+        let defs = crate::definition::Definitions::from_toml(
+            r#"
+            name = "grpc-client"
+            types = ["grpc"]
+            version = "v1.0.0"
+            language = "rust"
+            product = "tests"
+
+            [clients.greeter]
+            host = "http://127.0.0.1"
+            port = 1
+            "#,
+        )
+        .unwrap();
+
+        let env = crate::env::Env::load(&defs).unwrap();
+        let logger = Arc::new(crate::logger::builder::LoggerBuilder::new().build());
+        Arc::new(context::Context::new(env, logger, Arc::new(defs), vec![]))
+    }
+
+    #[tokio::test]
+    async fn test_link_grpc_service() {
+        async fn link(
+            ctx: Arc<context::Context>,
+        ) -> crate::errors::Result<GreeterClient<tonic::transport::Channel>> {
+            Ok(crate::link_grpc_service!(ctx, GreeterClient, "greeter"))
+        }
+
+        async fn link_with_destination(
+            ctx: Arc<context::Context>,
+        ) -> crate::errors::Result<GreeterClient<Channel>> {
+            Ok(crate::link_grpc_service!(
+                ctx,
+                GreeterClient,
+                "greeter",
+                with_destination
+            ))
+        }
+
+        let error = link(build_context()).await.err().unwrap();
+        assert!(error.to_string().contains(r#""kind":"CustomError""#));
+
+        let error = link_with_destination(build_context()).await.err().unwrap();
+        assert!(error.to_string().contains(r#""kind":"CustomError""#));
+    }
+
+    #[tokio::test]
+    async fn test_destination_on_transport_failure() {
+        let mut middleware = DestinationMiddleware::new(Unreachable, "greeter");
+        let error = middleware.call(http::Request::new(())).await.err().unwrap();
+        let status = error.downcast::<tonic::Status>().unwrap();
+
+        assert_eq!(status.message(), "connection refused");
+        assert_eq!(
+            status.metadata().get(DESTINATION_METADATA_KEY).unwrap(),
+            "greeter"
+        );
+    }
+}
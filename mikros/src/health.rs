@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+
+use serde_derive::Serialize;
+
+use crate::plugin;
+use crate::service::context::Context;
+
+/// The health status of the service or one of its components.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Starting,
+    Up,
+    Down,
+    Stopping,
+}
+
+/// The health information of a single service component, i.e., a feature or
+/// a service kind implementation.
+#[derive(Serialize, Clone, Debug)]
+pub struct ComponentHealth {
+    pub status: Status,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    fn new(status: Status) -> Self {
+        Self {
+            status,
+            error: None,
+        }
+    }
+
+    fn with_error(status: Status, error: &str) -> Self {
+        Self {
+            status,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// The result of a health probe with the service overall status and the
+/// status of each of its components.
+#[derive(Serialize, Clone, Debug)]
+pub struct Report {
+    pub status: Status,
+    pub components: BTreeMap<String, ComponentHealth>,
+}
+
+impl Report {
+    /// Returns if the probe succeeded or not.
+    pub fn is_up(&self) -> bool {
+        self.status == Status::Up
+    }
+}
+
+// Health keeps the current service state, updated by the framework while it
+// executes, to be used when answering the probes.
+pub(crate) struct Health {
+    status: RwLock<Status>,
+    components: RwLock<BTreeMap<String, ComponentHealth>>,
+    services: RwLock<Vec<Box<dyn plugin::service::Service>>>,
+}
+
+impl Health {
+    pub(crate) fn new() -> Self {
+        Self {
+            status: RwLock::new(Status::Starting),
+            components: RwLock::new(BTreeMap::new()),
+            services: RwLock::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn status(&self) -> Status {
+        *self.status.read().unwrap()
+    }
+
+    pub(crate) fn set_status(&self, status: Status) {
+        *self.status.write().unwrap() = status;
+    }
+
+    pub(crate) fn feature_name(name: &str) -> String {
+        format!("feature.{name}")
+    }
+
+    pub(crate) fn service_name(kind: &str) -> String {
+        format!("service.{kind}")
+    }
+
+    pub(crate) fn set_component(&self, name: &str, status: Status) {
+        self.components
+            .write()
+            .unwrap()
+            .insert(name.to_string(), ComponentHealth::new(status));
+    }
+
+    pub(crate) fn set_component_failure(&self, name: &str, error: &str) {
+        self.components.write().unwrap().insert(
+            name.to_string(),
+            ComponentHealth::with_error(Status::Down, error),
+        );
+    }
+
+    pub(crate) fn register_service(&self, service: Box<dyn plugin::service::Service>) {
+        let name = Self::service_name(&service.kind().to_string());
+
+        self.services.write().unwrap().push(service);
+        self.set_component(&name, Status::Starting);
+    }
+
+    fn has_failures(components: &BTreeMap<String, ComponentHealth>) -> bool {
+        components.values().any(|c| c.status == Status::Down)
+    }
+
+    // Liveness only fails if some service task has already failed. Features
+    // and health check callbacks are not considered here.
+    pub(crate) fn liveness(&self) -> Report {
+        let components: BTreeMap<String, ComponentHealth> = self
+            .components
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(name, c)| name.starts_with("service.") && c.status == Status::Down)
+            .map(|(name, c)| (name.clone(), c.clone()))
+            .collect();
+
+        Report {
+            status: if components.is_empty() {
+                Status::Up
+            } else {
+                Status::Down
+            },
+            components,
+        }
+    }
+
+    // Readiness is only succeeded when the service is running and every
+    // feature and service implementation reports itself as healthy.
+    pub(crate) async fn readiness(&self, ctx: Arc<Context>) -> Report {
+        let mut components = self.components.read().unwrap().clone();
+        let status = self.status();
+
+        if status != Status::Up || Self::has_failures(&components) {
+            return Report {
+                status: if status == Status::Up {
+                    Status::Down
+                } else {
+                    status
+                },
+                components,
+            };
+        }
+
+        // Features are cloned out of the lock, so health checks can use the
+        // context APIs that also access them.
+        let features: Vec<Box<dyn plugin::feature::Feature>> = ctx
+            .features
+            .lock()
+            .await
+            .iter()
+            .filter(|f| f.is_enabled())
+            .cloned()
+            .collect();
+
+        for feature in features {
+            let name = Self::feature_name(feature.name());
+            components.insert(name, Self::check(feature.health_check(ctx.clone()).await));
+        }
+
+        let services = self.services.read().unwrap().clone();
+        for service in services {
+            let name = Self::service_name(&service.kind().to_string());
+            components.insert(name, Self::check(service.health_check(ctx.clone()).await));
+        }
+
+        Report {
+            status: if Self::has_failures(&components) {
+                Status::Down
+            } else {
+                Status::Up
+            },
+            components,
+        }
+    }
+
+    fn check(result: crate::errors::Result<()>) -> ComponentHealth {
+        match result {
+            Ok(()) => ComponentHealth::new(Status::Up),
+            Err(e) => ComponentHealth::with_error(Status::Down, &e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::Definitions;
+    use crate::env::Env;
+    use crate::logger::builder::LoggerBuilder;
+    use mikros_tests::common::assets_path;
+
+    fn build_context() -> Arc<Context> {
+        let filename = assets_path().join("definitions/service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        let env = Env::load(&defs).unwrap();
+        let logger = Arc::new(LoggerBuilder::new().build());
+
+        Arc::new(Context::new(env, logger, defs, vec![]))
+    }
+
+    #[tokio::test]
+    async fn test_readiness_follows_service_status() {
+        let ctx = build_context();
+        assert_eq!(ctx.readiness().await.status, Status::Starting);
+
+        ctx.health.set_status(Status::Up);
+        assert!(ctx.readiness().await.is_up());
+
+        ctx.health
+            .set_component_failure("service.grpc", "transport failure");
+        assert_eq!(ctx.readiness().await.status, Status::Down);
+
+        ctx.health.set_status(Status::Stopping);
+        assert_eq!(ctx.readiness().await.status, Status::Stopping);
+    }
+
+    #[test]
+    fn test_liveness_with_service_failure() {
+        let health = Health::new();
+        health.set_component("feature.example", Status::Starting);
+        assert!(health.liveness().is_up());
+
+        health.set_component_failure("service.http", "could not bind");
+        let report = health.liveness();
+        assert!(!report.is_up());
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(
+            report.components["service.http"].error.as_deref(),
+            Some("could not bind")
+        );
+    }
+
+    #[derive(Clone)]
+    struct ReentrantFeature;
+
+    #[async_trait::async_trait]
+    impl plugin::feature::Feature for ReentrantFeature {
+        fn name(&self) -> &str {
+            "reentrant"
+        }
+
+        fn info(&self) -> Option<serde_json::Value> {
+            None
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn can_be_initialized(
+            &self,
+            _: Arc<Definitions>,
+            _: Arc<Env>,
+        ) -> crate::errors::Result<bool> {
+            Ok(true)
+        }
+
+        async fn initialize(&mut self, _: Arc<Context>) -> crate::errors::Result<()> {
+            Ok(())
+        }
+
+        async fn cleanup(&self) {}
+
+        fn health_check<'a>(
+            &'a self,
+            ctx: Arc<Context>,
+        ) -> crate::plugin::feature::BoxResultFuture<'a, ()> {
+            crate::plugin::feature::box_result(async move {
+                ctx.feature(self.name()).await.map(|_| ())
+            })
+        }
+
+        fn service_api(&self) -> Option<&dyn std::any::Any> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_readiness_with_health_check_using_context() {
+        let filename = assets_path().join("definitions/service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        let env = Env::load(&defs).unwrap();
+        let logger = Arc::new(LoggerBuilder::new().build());
+        let ctx = Arc::new(Context::new(
+            env,
+            logger,
+            defs,
+            vec![Box::new(ReentrantFeature)],
+        ));
+
+        ctx.health.set_status(Status::Up);
+        let report = tokio::time::timeout(std::time::Duration::from_secs(5), ctx.readiness())
+            .await
+            .expect("readiness probe deadlocked");
+
+        assert!(report.is_up());
+        assert!(report.components.contains_key("feature.reentrant"));
+    }
+}
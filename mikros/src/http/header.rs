@@ -5,6 +5,7 @@ use crate::service::context::Context;
 
 /// Responsible for retrieving a value from an HTTP header map and returning
 /// it as a bool.
+#[allow(clippy::result_large_err)]
 pub fn to_bool(
     ctx: Arc<Context>,
     headers: &http::HeaderMap<http::HeaderValue>,
@@ -31,6 +32,7 @@ pub fn to_bool(
 
 /// Responsible for retrieving a value from an HTTP header map and returning
 /// it as a String.
+#[allow(clippy::result_large_err)]
 pub fn to_string(
     ctx: Arc<Context>,
     headers: &http::HeaderMap<http::HeaderValue>,
@@ -1,13 +1,19 @@
+// Allows code generated by our own macros, which uses `mikros::` paths, to
+// also be used inside the crate.
+extern crate self as mikros;
+
+pub mod args;
 pub mod definition;
 pub mod env;
 pub mod errors;
+pub mod grpc;
+pub mod health;
 pub mod http;
 pub mod logger;
 pub mod plugin;
+pub mod secrets;
 pub mod service;
-
-mod args;
-mod grpc;
+pub mod tls;
 
 // Forward some declarations for applications. Most of the time, applications
 // will just use us as their dependencies, or at least for their main parts.
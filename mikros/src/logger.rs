@@ -42,7 +42,7 @@ impl FromStr for Level {
         match s {
             "debug" => Ok(Level::Debug),
             "info" => Ok(Level::Info),
-            "warning" => Ok(Level::Warning),
+            "warning" | "warn" => Ok(Level::Warning),
             "error" => Ok(Level::Error),
             _ => Err(format!("unknown log level {s}")),
         }
@@ -55,9 +55,11 @@ impl Logger {
         let env_filter = EnvFilter::new(level.to_string());
         let (filter_layer, reload) = tracing_subscriber::reload::Layer::new(env_filter);
 
-        // Do not initialize the global subscriber if we're running tests.
+        // Do not initialize the global subscriber if we're running tests. If
+        // more than one service runs inside the same process, the subscriber
+        // of the first one is kept.
         if !cfg!(test) {
-            tracing_subscriber::registry()
+            let _ = tracing_subscriber::registry()
                 .with(
                     LayerBuilder::new()
                         .with_local_timestamp(builder.local_timestamp)
@@ -65,7 +67,7 @@ impl Logger {
                         .build(),
                 )
                 .with(filter_layer)
-                .init();
+                .try_init();
         }
 
         Self { reload }
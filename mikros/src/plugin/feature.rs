@@ -3,8 +3,9 @@ use std::sync::Arc;
 use core::{future::Future, pin::Pin};
 
 use crate::definition::Definitions;
-use crate::env::Env;
+use crate::env::{Env, Variable};
 use crate::errors;
+use crate::secrets::SecretsProvider;
 use crate::service::context::Context;
 
 /// Feature is a set of methods that every feature must implement to be supported
@@ -60,12 +61,41 @@ pub trait Feature: Send + FeatureClone + std::any::Any {
     fn is_enabled(&self) -> bool;
 
     /// Checks if the feature can be initialized or not.
+    #[allow(clippy::result_large_err)]
     fn can_be_initialized(
         &self,
         definitions: Arc<Definitions>,
         envs: Arc<Env>,
     ) -> errors::Result<bool>;
 
+    /// Checks if the feature settings inside the service definitions are
+    /// valid, usually through the `Definitions::try_load_feature_validated`
+    /// API. It is called before features are initialized and when definitions
+    /// are reloaded, so invalid settings fail fast. By default, it always
+    /// succeeds.
+    #[allow(clippy::result_large_err)]
+    fn validate_definitions(&self, _definitions: &Definitions) -> errors::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the JSON Schema of the feature settings, i.e., its
+    /// `[features.<name>]` section, to be included in the schema printed by
+    /// the `--print-schema` option.
+    fn definitions_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+
+    /// Declares the environment variables used by the feature, usually
+    /// through the `env_variables` function of a structure deriving the `Env`
+    /// macro. They are loaded before the feature is initialized, failing if
+    /// required ones are not set, and are available through the
+    /// `Context::feature_env` API. They are also displayed by the `--print-env`
+    /// option. The `suffix` is the one used by service-specific variables,
+    /// i.e., the service name in snake_case.
+    fn env_variables(&self, _suffix: &str) -> Vec<Variable> {
+        Vec::new()
+    }
+
     /// Initializes everything the feature needs to run. Also, here is the place
     /// where, if it needs, some task should be put to execute.
     async fn initialize(&mut self, ctx: Arc<Context>) -> errors::Result<()>;
@@ -73,6 +103,40 @@ pub trait Feature: Send + FeatureClone + std::any::Any {
     /// Release resources from the feature.
     async fn cleanup(&self);
 
+    /// Checks if the feature is healthy and able to be used by the service.
+    /// It is called by the service readiness probe and, by default, always
+    /// succeeds.
+    ///
+    /// Since the returned future must be `Send`, features that use `self`
+    /// inside it must also be `Sync`. It is usually implemented with the
+    /// `feature_async!` macro:
+    ///
+    /// ```ignore
+    /// fn health_check<'a>(&'a self, ctx: Arc<Context>) -> BoxResultFuture<'a, ()> {
+    ///     mikros::feature_async!(self.ping(ctx).await)
+    /// }
+    /// ```
+    fn health_check<'a>(&'a self, _ctx: Arc<Context>) -> BoxResultFuture<'a, ()> {
+        box_result(async { Ok(()) })
+    }
+
+    /// Called when reloaded service definitions change the feature settings,
+    /// so it can apply them without restarting the service. By default, new
+    /// settings are ignored.
+    async fn on_definitions_changed(
+        &mut self,
+        _ctx: Arc<Context>,
+        _definitions: Arc<Definitions>,
+    ) -> errors::Result<()> {
+        Ok(())
+    }
+
+    /// Returns a secrets provider implemented by the feature, which is used
+    /// to resolve secrets through the `Context::secret` API.
+    fn secrets_provider(&self) -> Option<Arc<dyn SecretsProvider>> {
+        None
+    }
+
     /// Returns the feature API that should be used by services and applications.
     fn service_api(&self) -> Option<&dyn std::any::Any>;
 }
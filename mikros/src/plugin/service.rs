@@ -23,6 +23,7 @@ pub trait Service: ServiceClone + Lifecycle {
     fn mode(&self) -> ServiceExecutionMode;
 
     /// Initializes everything that the service implementation needs to run.
+    #[allow(clippy::result_large_err)]
     fn initialize(
         &mut self,
         ctx: Arc<Context>,
@@ -31,12 +32,35 @@ pub trait Service: ServiceClone + Lifecycle {
         options: HashMap<String, serde_json::Value>,
     ) -> errors::Result<()>;
 
+    /// Checks if the service settings inside the service definitions, usually
+    /// its `[services.<kind>]` section, are valid. It is called before the
+    /// service is initialized and by the `--check-config` option. By default,
+    /// it always succeeds.
+    #[allow(clippy::result_large_err)]
+    fn validate_definitions(&self, _definitions: &definition::Definitions) -> errors::Result<()> {
+        Ok(())
+    }
+
+    /// Returns the JSON Schema of the service settings, i.e., its
+    /// `[services.<kind>]` section, to be included in the schema printed by
+    /// the `--print-schema` option.
+    fn definitions_schema(&self) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Puts the service implementation to run.
     async fn run(&mut self, ctx: Arc<Context>, shutdown_rx: watch::Receiver<()>) -> errors::Result<()>;
 
     /// Stops the current service implementation. The place to let the service
     /// execute its graceful shutdown.
     async fn stop(&self, ctx: Arc<Context>);
+
+    /// Checks if the service implementation is healthy and able to handle
+    /// requests. It is called by the service readiness probe and, by default,
+    /// always succeeds.
+    async fn health_check(&self, _ctx: Arc<Context>) -> errors::Result<()> {
+        Ok(())
+    }
 }
 
 pub trait ServiceClone {
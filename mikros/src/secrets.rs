@@ -0,0 +1,285 @@
+pub(crate) mod errors;
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::definition::Definitions;
+use crate::errors as merrors;
+
+/// A secret value. It is never displayed, neither by its `Debug` and
+/// `Display` implementations nor when serialized, so it can't leak into
+/// logs. Its content must be explicitly accessed with the `expose` API.
+#[derive(Clone, PartialEq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn new(value: &str) -> Self {
+        Self(value.to_string())
+    }
+
+    /// Gives access to the secret content.
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(<redacted>)")
+    }
+}
+
+impl Display for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<redacted>")
+    }
+}
+
+impl serde::Serialize for Secret {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str("<redacted>")
+    }
+}
+
+/// SecretsProvider is the API that every secrets backend must implement.
+/// Features can provide their own backends, like a vault, through the
+/// `Feature::secrets_provider` API.
+#[async_trait::async_trait]
+pub trait SecretsProvider: Send + Sync {
+    /// The provider name, used to select it inside the `secrets.providers`
+    /// setting.
+    fn name(&self) -> &str;
+
+    /// Retrieves a secret, returning `Ok(None)` when the provider does not
+    /// have it.
+    async fn get(&self, name: &str) -> merrors::Result<Option<Secret>>;
+}
+
+/// A provider that retrieves secrets from files inside a directory, where
+/// each file holds a single secret, like Kubernetes secret volumes.
+pub struct FileProvider {
+    directory: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(directory: &str) -> Self {
+        Self {
+            directory: PathBuf::from(directory),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SecretsProvider for FileProvider {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    async fn get(&self, name: &str) -> merrors::Result<Option<Secret>> {
+        // Secret names can't point outside the directory.
+        if name.is_empty() || name.contains(['/', '\\']) || name == ".." {
+            return Ok(None);
+        }
+
+        match tokio::fs::read_to_string(self.directory.join(name)).await {
+            Ok(content) => Ok(Some(Secret::new(content.trim_end_matches(['\r', '\n'])))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(Resolver::error(errors::Error::CouldNotRead(
+                name.to_string(),
+                e.to_string(),
+            ))),
+        }
+    }
+}
+
+/// A provider that retrieves secrets from environment variables with the
+/// same name. Values explicitly set, like the ones from the
+/// `ServiceBuilder::with_env` API, take precedence over the process
+/// environment.
+pub struct EnvProvider {
+    envs: HashMap<String, String>,
+}
+
+impl EnvProvider {
+    pub fn new(envs: HashMap<String, String>) -> Self {
+        Self { envs }
+    }
+}
+
+#[async_trait::async_trait]
+impl SecretsProvider for EnvProvider {
+    fn name(&self) -> &str {
+        "env"
+    }
+
+    async fn get(&self, name: &str) -> merrors::Result<Option<Secret>> {
+        Ok(self
+            .envs
+            .get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+            .map(|v| Secret::new(&v)))
+    }
+}
+
+// Resolver looks for secrets using the configured providers, in order.
+pub(crate) struct Resolver {
+    order: Option<Vec<String>>,
+    builtin: Vec<Arc<dyn SecretsProvider>>,
+}
+
+impl Resolver {
+    pub(crate) fn new(definitions: &Definitions, envs: HashMap<String, String>) -> Self {
+        let settings = definitions.secrets();
+        let mut builtin: Vec<Arc<dyn SecretsProvider>> = Vec::new();
+
+        if let Some(directory) = &settings.directory {
+            builtin.push(Arc::new(FileProvider::new(directory)));
+        }
+
+        builtin.push(Arc::new(EnvProvider::new(envs)));
+
+        Self {
+            order: settings.providers,
+            builtin,
+        }
+    }
+
+    // Resolves a secret using the builtin providers and the ones from
+    // features. Without an explicit order, builtin providers are used first.
+    pub(crate) async fn resolve(
+        &self,
+        name: &str,
+        features: Vec<Arc<dyn SecretsProvider>>,
+    ) -> merrors::Result<Option<Secret>> {
+        let mut providers: HashMap<String, Arc<dyn SecretsProvider>> = HashMap::new();
+        let mut default_order = Vec::new();
+
+        for provider in self.builtin.iter().cloned().chain(features) {
+            default_order.push(provider.name().to_string());
+            providers.insert(provider.name().to_string(), provider);
+        }
+
+        for provider_name in self.order.as_ref().unwrap_or(&default_order) {
+            let Some(provider) = providers.get(provider_name) else {
+                return Err(Self::error(errors::Error::ProviderNotFound(
+                    provider_name.clone(),
+                )));
+            };
+
+            if let Some(secret) = provider.get(name).await? {
+                return Ok(Some(secret));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn error(e: errors::Error) -> merrors::ServiceError {
+        let error: merrors::Error = e.into();
+        error.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Vault;
+
+    #[async_trait::async_trait]
+    impl SecretsProvider for Vault {
+        fn name(&self) -> &str {
+            "vault"
+        }
+
+        async fn get(&self, name: &str) -> merrors::Result<Option<Secret>> {
+            Ok((name == "DB_PASSWORD").then(|| Secret::new("from-vault")))
+        }
+    }
+
+    fn definitions(secrets: &str) -> Definitions {
+        Definitions::from_toml(&format!(
+            r#"
+            name = "my-service"
+            types = ["grpc"]
+            version = "v1.0.0"
+            language = "rust"
+            product = "incredible-product"
+            {secrets}
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_secret_is_redacted() {
+        let secret = Secret::new("admin123");
+        assert_eq!(secret.expose(), "admin123");
+        assert_eq!(format!("{secret}"), "<redacted>");
+        assert_eq!(format!("{secret:?}"), "Secret(<redacted>)");
+        assert_eq!(
+            serde_json::json!({ "password": secret }).to_string(),
+            r#"{"password":"<redacted>"}"#
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_provider() {
+        let directory = std::env::temp_dir().join(format!("mikros-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        std::fs::write(directory.join("api_key"), "abc123\n").unwrap();
+
+        let provider = FileProvider::new(directory.to_str().unwrap());
+        let secret = provider.get("api_key").await.unwrap();
+        assert_eq!(secret.unwrap().expose(), "abc123");
+        assert!(provider.get("unknown").await.unwrap().is_none());
+        assert!(provider.get("../api_key").await.unwrap().is_none());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_env_provider_with_explicit_envs() {
+        let envs = HashMap::from([("MIKROS_EXPLICIT_SECRET".to_string(), "explicit".to_string())]);
+
+        let provider = EnvProvider::new(envs);
+        let secret = provider.get("MIKROS_EXPLICIT_SECRET").await.unwrap();
+        assert_eq!(secret.unwrap().expose(), "explicit");
+        assert!(
+            provider
+                .get("MIKROS_UNKNOWN_SECRET")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_order() {
+        let features: Vec<Arc<dyn SecretsProvider>> = vec![Arc::new(Vault)];
+
+        let resolver = Resolver::new(&definitions(""), HashMap::new());
+        let secret = resolver.resolve("DB_PASSWORD", features.clone()).await;
+        assert_eq!(secret.ok().flatten().unwrap().expose(), "from-vault");
+
+        let resolver = Resolver::new(
+            &definitions("[secrets]\nproviders = [\"env\"]"),
+            HashMap::new(),
+        );
+        let secret = resolver.resolve("DB_PASSWORD", features.clone()).await;
+        assert!(secret.ok().flatten().is_none());
+
+        let resolver = Resolver::new(
+            &definitions("[secrets]\nproviders = [\"aws\"]"),
+            HashMap::new(),
+        );
+        let secret = resolver.resolve("DB_PASSWORD", features).await;
+        assert!(secret.is_err());
+    }
+}
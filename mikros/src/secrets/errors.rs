@@ -0,0 +1,8 @@
+// Module internal errors
+crate::module_errors!(
+    Error {
+        SecretNotFound(s: String) => "secret '{}' not found",
+        ProviderNotFound(p: String) => "secrets provider '{}' not found",
+        CouldNotRead(s: String, e: String) => "could not read secret '{}': {}"
+    }
+);
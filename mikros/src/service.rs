@@ -1,26 +1,32 @@
 mod errors;
 
 pub mod builder;
+mod config;
 pub mod context;
 pub mod grpc;
 pub mod http;
 pub mod lifecycle;
+pub(crate) mod listener;
 pub mod native;
+mod reload;
 pub mod script;
 
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 
 use tokio::signal;
-use tokio::sync::watch;
+use tokio::sync::{oneshot, watch};
 use tokio::task::{self, JoinHandle};
+use tokio::time;
 
 use crate::args::Args;
 use crate::definition::{CustomServiceInfo, Definitions, ServiceKind};
 use crate::env::Env;
+use crate::health::{Health, Status};
 use crate::plugin::service::ServiceExecutionMode;
-use crate::service::builder::ServiceBuilder;
-use crate::{errors as merrors, logger, plugin};
+use crate::service::builder::{DefinitionsCheck, ServiceBuilder};
+use crate::{errors as merrors, logger, plugin, secrets};
 
 pub struct Service {
     envs: Arc<Env>,
@@ -28,17 +34,152 @@ pub struct Service {
     logger: Arc<logger::Logger>,
     servers: HashMap<String, Box<dyn plugin::service::Service>>,
     context: context::Context,
-    handlers: Vec<JoinHandle<()>>,
+    handlers: Vec<(ServiceKind, JoinHandle<()>)>,
     shutdown_tx: watch::Sender<()>,
     service_options: HashMap<String, serde_json::Value>,
+    watcher: Option<reload::Watcher>,
+    definitions_check: Option<DefinitionsCheck>,
+    explicit_envs: HashMap<String, String>,
+}
+
+/// A handle to a service running in background, started by the
+/// `Service::spawn` API.
+pub struct ServiceHandle {
+    context: Arc<context::Context>,
+    shutdown_tx: oneshot::Sender<()>,
+    task: JoinHandle<merrors::Result<()>>,
+}
+
+impl ServiceHandle {
+    /// Gives access to the running service context.
+    pub fn context(&self) -> Arc<context::Context> {
+        self.context.clone()
+    }
+
+    /// Requests the service to stop, like if it had received a SIGTERM, and
+    /// waits until it finishes.
+    ///
+    /// # Errors
+    ///
+    /// It returns the same errors that `Service::start` returns when the
+    /// service finishes.
+    pub async fn shutdown(self) -> merrors::Result<()> {
+        let _ = self.shutdown_tx.send(());
+        Self::join(self.context, self.task).await
+    }
+
+    /// Waits until the service finishes by itself, which happens when one of
+    /// its tasks fails or, for non-blocking services, when their execution
+    /// ends.
+    ///
+    /// # Errors
+    ///
+    /// It returns the same errors that `Service::start` returns when the
+    /// service finishes.
+    pub async fn wait(self) -> merrors::Result<()> {
+        // Keeps the channel open, since dropping it stops the service.
+        let _shutdown_tx = self.shutdown_tx;
+        Self::join(self.context, self.task).await
+    }
+
+    async fn join(
+        context: Arc<context::Context>,
+        task: JoinHandle<merrors::Result<()>>,
+    ) -> merrors::Result<()> {
+        match task.await {
+            Ok(result) => result,
+            Err(e) => Err(merrors::ServiceError::internal(context, &e.to_string())),
+        }
+    }
 }
 
 impl Service {
-    pub(crate) fn new(builder: ServiceBuilder) -> Result<Self, merrors::Error> {
-        let definitions = Service::load_definitions(&builder)?;
+    pub(crate) fn new(mut builder: ServiceBuilder) -> Result<Self, merrors::Error> {
+        // Values explicitly set through the builder take precedence over the
+        // process environment.
+        let explicit_envs = std::mem::take(&mut builder.envs);
+        let source = |name: &str| {
+            explicit_envs
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok())
+        };
+
+        // Definitions given by the application skip the command line
+        // arguments, since the process may not belong to the service.
+        let args = builder
+            .definitions
+            .is_none()
+            .then(|| Args::load(&builder.flags));
+
+        if let Some(args) = &args {
+            if args.print_schema {
+                let schema = config::schema(&builder.features, &builder.servers);
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&schema).unwrap_or_default()
+                );
+                std::process::exit(0);
+            }
+
+            if args.check_config {
+                let code = Self::check_config(&mut builder, args, &source);
+                std::process::exit(code);
+            }
+        }
+
+        let definitions = Service::load_definitions(&mut builder, args.as_ref(), &source)?;
+        if args.as_ref().is_some_and(|args| args.version) {
+            println!(
+                "{} {} ({})",
+                &*definitions.name, definitions.version, definitions.product
+            );
+            std::process::exit(0);
+        }
+
+        // Variables are printed before being loaded, so missing ones can also
+        // be found.
+        if let Some(format) = args.as_ref().and_then(|args| args.print_env.as_deref()) {
+            let variables = config::variables(&definitions, &builder.features);
+            match format {
+                "json" => println!(
+                    "{}",
+                    serde_json::to_string_pretty(&config::variables_json(&variables))
+                        .unwrap_or_default()
+                ),
+                _ => print!("{}", config::variables_table(&variables)),
+            }
+
+            std::process::exit(0);
+        }
+
         let logger = Self::start_logger(&definitions);
         let (shutdown_tx, _) = watch::channel(());
-        let envs = Env::load(&definitions)?;
+        let envs = Env::load_from_source(&definitions, &source)?;
+
+        if args.as_ref().is_some_and(|args| args.print_config) {
+            let config = config::effective(&definitions, &envs);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&config).unwrap_or_default()
+            );
+            std::process::exit(0);
+        }
+
+        // Only definitions loaded from files can be reloaded.
+        let watcher = builder
+            .reload_interval
+            .zip(args.as_ref())
+            .map(|(interval, args)| {
+                reload::Watcher::new(
+                    args.config_paths.clone(),
+                    args.overrides.clone(),
+                    interval,
+                    explicit_envs.clone(),
+                    builder.custom_service_types.clone(),
+                    builder.definitions_check.clone(),
+                )
+            });
 
         let mut features = vec![];
         for f in builder.features.clone() {
@@ -49,16 +190,29 @@ impl Service {
             envs: envs.clone(),
             definitions: definitions.clone(),
             logger: logger.clone(),
-            context: Self::build_context(envs.clone(), logger, definitions, features),
+            context: Self::build_context(
+                envs.clone(),
+                logger,
+                definitions,
+                features,
+                args.map(|args| args.flags).unwrap_or_default(),
+                &explicit_envs,
+            ),
             servers: builder.servers,
             handlers: Vec::new(),
             shutdown_tx,
             service_options: builder.service_options,
+            watcher,
+            definitions_check: builder.definitions_check,
+            explicit_envs,
         })
     }
 
-    fn load_definitions(builder: &ServiceBuilder) -> Result<Arc<Definitions>, merrors::Error> {
-        let args = Args::load();
+    fn load_definitions(
+        builder: &mut ServiceBuilder,
+        args: Option<&Args>,
+        source: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Arc<Definitions>, merrors::Error> {
         let mut custom_info: Option<CustomServiceInfo> = None;
 
         if !builder.custom_service_types.is_empty() {
@@ -67,27 +221,76 @@ impl Service {
             });
         }
 
-        Ok(Definitions::new(args.config_path.as_deref(), custom_info)?)
+        let (definitions, overrides) = match (builder.definitions.take(), args) {
+            (Some(definitions), _) => (definitions, &[][..]),
+            (None, Some(args)) => (
+                Definitions::load(&args.config_paths)?,
+                args.overrides.as_slice(),
+            ),
+            (None, None) => (Definitions::load(&[])?, &[][..]),
+        };
+
+        let deployment = Env::load_deployment(&definitions, source);
+        let definitions = definitions
+            .with_deployment(&deployment)?
+            .with_overrides(overrides)?
+            .interpolate(source)?;
+
+        definitions.validate(custom_info)?;
+        Ok(Arc::new(definitions))
+    }
+
+    // Loads and validates the definitions, printing every problem found, and
+    // returns the process exit code.
+    fn check_config(
+        builder: &mut ServiceBuilder,
+        args: &Args,
+        source: &dyn Fn(&str) -> Option<String>,
+    ) -> i32 {
+        let problems = match Self::load_definitions(builder, Some(args), source) {
+            Err(e) => vec![merrors::ServiceError::from(e).description()],
+            Ok(definitions) => config::check(
+                &definitions,
+                &builder.features,
+                &builder.servers,
+                builder.definitions_check.as_ref(),
+            ),
+        };
+
+        if problems.is_empty() {
+            println!("service definitions are valid");
+            return 0;
+        }
+
+        for problem in &problems {
+            eprintln!("error: {problem}");
+        }
+
+        1
     }
 
     fn start_logger(defs: &Definitions) -> Arc<logger::Logger> {
         let log = defs.log();
+        let level = log.level.unwrap();
+        let parsed = level.parse::<logger::Level>();
 
-        Arc::new(
-            logger::builder::LoggerBuilder::new()
-                .with_level(
-                    log.level
-                        .unwrap()
-                        .parse::<logger::Level>()
-                        .unwrap_or(logger::Level::Info),
-                )
-                .with_local_timestamp(log.local_timestamp.unwrap())
-                .with_field("svc.name", &defs.name)
-                .with_field("svc.version", &defs.version)
-                .with_field("svc.product", &defs.product)
-                .with_field("svc.language", &defs.language)
-                .build(),
-        )
+        let logger = logger::builder::LoggerBuilder::new()
+            .with_level(parsed.clone().unwrap_or(logger::Level::Info))
+            .with_local_timestamp(log.local_timestamp.unwrap())
+            .with_field("svc.name", &defs.name)
+            .with_field("svc.version", &defs.version)
+            .with_field("svc.product", &defs.product)
+            .with_field("svc.language", &defs.language)
+            .build();
+
+        if let Err(e) = parsed {
+            logger.warningf(
+                "using info log level",
+                serde_json::json!({ "error": e, "level": level }),
+            );
+        }
+
+        Arc::new(logger)
     }
 
     fn build_context(
@@ -95,8 +298,13 @@ impl Service {
         logger: Arc<logger::Logger>,
         defs: Arc<Definitions>,
         features: Vec<Box<dyn plugin::feature::Feature>>,
+        flags: HashMap<String, Option<String>>,
+        explicit_envs: &HashMap<String, String>,
     ) -> context::Context {
-        context::Context::new(envs, logger, defs, features)
+        let mut context = context::Context::new(envs, logger, defs.clone(), features);
+        context.flags = Arc::new(flags);
+        context.secrets = Arc::new(secrets::Resolver::new(&defs, explicit_envs.clone()));
+        context
     }
 
     /// Puts the service to run.
@@ -105,17 +313,88 @@ impl Service {
     ///
     /// It will return an `Err` if service definitions could not be validated
     /// or something wrong happens during its initialization.
+    ///
+    /// Once running, the service is stopped by SIGINT, SIGTERM or SIGQUIT. If
+    /// a service task fails, or the service does not finish within the
+    /// `shutdown.drain_timeout` setting, the process exits with a non-zero
+    /// status, since aborted tasks could keep it from finishing.
     pub async fn start(&mut self) -> merrors::Result<()> {
+        self.initialize().await?;
+
+        if let Err(e) = self.run(Self::wait_shutdown_signal()).await {
+            self.logger.errorf(
+                "service finished with error",
+                serde_json::json!({
+                    "error": e.to_string(),
+                }),
+            );
+
+            std::process::exit(1);
+        }
+
+        Ok(())
+    }
+
+    /// Starts the service in background, without taking over the process: it
+    /// does not handle OS signals and must be stopped through the returned
+    /// handle. It only returns after the service is initialized, so servers
+    /// are already bound and their addresses are available through the
+    /// `Context::bound_address` API.
+    ///
+    /// # Errors
+    ///
+    /// It will return an `Err` if service definitions could not be validated
+    /// or something wrong happens during its initialization.
+    pub async fn spawn(mut self) -> merrors::Result<ServiceHandle> {
+        self.initialize().await?;
+
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let context: Arc<context::Context> = self.context.clone().into();
+        let task = task::spawn(async move {
+            self.run(async {
+                // A dropped handle also stops the service.
+                let _ = shutdown_rx.await;
+                Ok("shutdown request")
+            })
+            .await
+        });
+
+        Ok(ServiceHandle {
+            context,
+            shutdown_tx,
+            task,
+        })
+    }
+
+    async fn initialize(&mut self) -> merrors::Result<()> {
         self.logger.info("service starting");
 
         if let Err(e) = self.validate_definitions() {
             return Err(e.into());
         }
 
+        let context: Arc<context::Context> = self.context.clone().into();
+        context
+            .check_definitions(&self.definitions, self.definitions_check.as_ref())
+            .await?;
+
+        for server in self.servers.values() {
+            server.validate_definitions(&self.definitions)?;
+        }
+
+        self.start_health_server().await?;
         self.start_features().await?;
         self.initialize_service_internals().await?;
         self.print_service_resources().await;
-        self.run().await
+        self.start_definitions_watcher();
+        Ok(())
+    }
+
+    fn start_definitions_watcher(&mut self) {
+        if let Some(watcher) = self.watcher.take() {
+            let context: Arc<context::Context> = self.context.clone().into();
+            task::spawn(watcher.run(context, self.shutdown_tx.subscribe()));
+        }
     }
 
     fn validate_definitions(&self) -> Result<(), merrors::Error> {
@@ -142,9 +421,44 @@ impl Service {
         modes.iter().all(|m| *m == modes[0])
     }
 
+    // Starts a standalone server for the health probes, if an address for it
+    // was set. This way, any kind of service is able to expose them.
+    async fn start_health_server(&mut self) -> merrors::Result<()> {
+        let context: Arc<context::Context> = self.context.clone().into();
+        let address = listener::resolve_health_address(&self.envs)
+            .map_err(|e| merrors::ServiceError::from_error(context.clone(), e))?;
+
+        let Some(address) = address else {
+            return Ok(());
+        };
+
+        let server = http::health::bind(context.clone(), &address, self.shutdown_tx.subscribe())
+            .await
+            .map_err(|e| merrors::ServiceError::from_error(context, e.into()))?;
+
+        self.logger.infof(
+            "health server is running",
+            serde_json::json!({
+                "svc.address": address.to_string(),
+            }),
+        );
+
+        task::spawn(server);
+        Ok(())
+    }
+
     async fn start_features(&mut self) -> merrors::Result<()> {
         self.logger.info("starting features");
-        self.context.initialize_features().await
+
+        let explicit_envs = &self.explicit_envs;
+        let source = |name: &str| {
+            explicit_envs
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok())
+        };
+
+        self.context.initialize_features(&source).await
     }
 
     async fn initialize_service_internals(&mut self) -> merrors::Result<()> {
@@ -164,6 +478,7 @@ impl Service {
             )?;
 
             svc.on_start(ctx.clone().into()).await?;
+            ctx.health.register_service(svc.clone());
         }
 
         Ok(())
@@ -184,7 +499,10 @@ impl Service {
         );
     }
 
-    async fn run(&mut self) -> merrors::Result<()> {
+    async fn run<F>(&mut self, shutdown_signal: F) -> merrors::Result<()>
+    where
+        F: Future<Output = merrors::Result<&'static str>>,
+    {
         let definitions = self.definitions.clone();
         let context = self.context.clone();
         let shutdown_tx = self.shutdown_tx.clone();
@@ -206,7 +524,13 @@ impl Service {
                     }),
                 );
 
+                let component = Health::service_name(&svc.kind().to_string());
+                context.health.set_component(&component, Status::Up);
+
                 if let Err(e) = svc.run(context.clone().into(), shutdown_rx.clone()).await {
+                    context
+                        .health
+                        .set_component_failure(&component, &e.to_string());
                     let _ = tx.send(e).await;
                     return;
                 }
@@ -227,27 +551,37 @@ impl Service {
                 );
             });
 
-            self.handlers.push(handle);
+            self.handlers.push((s.0.clone(), handle));
         }
 
+        self.context.health.set_status(Status::Up);
+
         // keep running until ctrl+c
         tokio::select! {
             Some(err) = rx.recv() => {
                 self.logger.error(&err.to_string());
-                self.stop_service_tasks().await?;
 
-                // Return the service handler error for the caller.
+                // The service handler error is the one returned to the
+                // caller, even if the service also fails to stop.
+                if let Err(e) = self.stop_service_tasks().await {
+                    self.logger.error(&e.to_string());
+                }
+
                 return Err(err);
             }
-            () = self.wait_finishing_signal() => {
+            result = self.wait_finishing_signal(shutdown_signal) => {
                 self.stop_service_tasks().await?;
+                result?;
             }
         }
 
         Ok(())
     }
 
-    async fn wait_finishing_signal(&self) {
+    async fn wait_finishing_signal<F>(&self, shutdown_signal: F) -> merrors::Result<()>
+    where
+        F: Future<Output = merrors::Result<&'static str>>,
+    {
         // If we are here is because we already passed the validation, and since
         // we only execute when execution modes are equal for all servers, it does
         // not matter which one we get.
@@ -255,18 +589,73 @@ impl Service {
 
         // Wait for a signal to finish the service.
         if mode == ServiceExecutionMode::Block {
-            signal::ctrl_c().await.expect("failed to listen for ctrl-c");
+            let name = shutdown_signal.await?;
+            self.logger.infof(
+                "received shutdown signal",
+                serde_json::json!({
+                    "signal": name,
+                }),
+            );
         }
+
+        Ok(())
+    }
+
+    // Waits until one of the signals that should finish the service arrives
+    // and returns its name.
+    #[cfg(unix)]
+    async fn wait_shutdown_signal() -> merrors::Result<&'static str> {
+        use signal::unix::{SignalKind, signal};
+
+        let listen = |kind: SignalKind, name: &str| {
+            signal(kind).map_err(|e| {
+                let error: merrors::Error =
+                    errors::Error::SignalListener(name.to_string(), e.to_string()).into();
+                error
+            })
+        };
+
+        let mut sigterm = listen(SignalKind::terminate(), "SIGTERM")?;
+        let mut sigquit = listen(SignalKind::quit(), "SIGQUIT")?;
+
+        Ok(tokio::select! {
+            _ = signal::ctrl_c() => "SIGINT",
+            _ = sigterm.recv() => "SIGTERM",
+            _ = sigquit.recv() => "SIGQUIT",
+        })
+    }
+
+    #[cfg(not(unix))]
+    async fn wait_shutdown_signal() -> merrors::Result<&'static str> {
+        if let Err(e) = signal::ctrl_c().await {
+            let error: merrors::Error =
+                errors::Error::SignalListener("SIGINT".to_string(), e.to_string()).into();
+            return Err(error.into());
+        }
+
+        Ok("SIGINT")
     }
 
     async fn stop_service_tasks(&mut self) -> merrors::Result<()> {
         let definitions = self.definitions.clone();
         let context = self.context.clone();
+        let timeout = definitions.shutdown().drain_timeout();
+        let mut hung: Vec<String> = Vec::new();
+
+        // Every step shares the same drain budget.
+        let deadline = time::Instant::now() + timeout;
+
+        self.context.health.set_status(Status::Stopping);
 
         // Call service stop callback so the service can stop itself
         for s in &definitions.types {
             let svc = self.get_server(&s.0)?;
-            svc.stop(context.clone().into()).await;
+            if time::timeout_at(deadline, svc.stop(context.clone().into()))
+                .await
+                .is_err()
+            {
+                hung.push(format!("service stop ({})", s.0));
+            }
         }
 
         // Tells the main tasks to stop
@@ -274,20 +663,42 @@ impl Service {
         self.logger
             .debug("sending shutdown signal for service tasks");
 
-        for handle in &mut self.handlers {
-            let _ = handle.await;
+        for (kind, handle) in &mut self.handlers {
+            if time::timeout_at(deadline, &mut *handle).await.is_err() {
+                handle.abort();
+                hung.push(format!("service task ({kind})"));
+            }
         }
 
         // Calls the callback to release service resources.
         for s in &definitions.types {
             let svc = self.get_server(&s.0)?;
-            svc.on_finish().await?;
+            match time::timeout_at(deadline, svc.on_finish()).await {
+                Ok(result) => result?,
+                Err(_) => hung.push(format!("service on_finish ({})", s.0)),
+            }
         }
 
         // Cleanup features
-        self.context.cleanup_features().await;
-        self.logger.info("service stopped");
+        let features = self.context.cleanup_features(deadline).await;
+        hung.extend(features.iter().map(|f| format!("feature cleanup ({f})")));
+
+        if !hung.is_empty() {
+            for name in &hung {
+                self.logger.errorf(
+                    "shutdown timeout exceeded, aborting",
+                    serde_json::json!({
+                        "name": name,
+                        "timeout": timeout.as_secs(),
+                    }),
+                );
+            }
 
+            let error: merrors::Error = errors::Error::ShutdownTimeout(hung.join(", ")).into();
+            return Err(error.into());
+        }
+
+        self.logger.info("service stopped");
         Ok(())
     }
 
@@ -301,3 +712,353 @@ impl Service {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::service::Address;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const DEFINITIONS: &str = r#"
+        name = "in-process-service"
+        types = ["http:0"]
+        version = "v1.0.0"
+        language = "rust"
+        product = "tests"
+
+        [service]
+        max_items = 0
+        "#;
+
+    #[tokio::test]
+    async fn test_spawn_and_shutdown() {
+        let definitions = Definitions::from_toml(DEFINITIONS).unwrap();
+
+        let handle = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(definitions)
+            .with_env("MIKROS_SERVICE_DEPLOY", "test")
+            .build()
+            .unwrap()
+            .spawn()
+            .await
+            .unwrap();
+
+        let ctx = handle.context();
+        assert_eq!(ctx.env_ref().deployment_env, "test");
+
+        let Some(Address::Socket(address)) = ctx.bound_address(&ServiceKind::Http) else {
+            panic!("http server address not available");
+        };
+
+        let mut stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(b"GET /health HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+
+        assert!(handle.shutdown().await.is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_health_server_with_address() {
+        let path = std::env::temp_dir().join(format!("mikros-health-{}.sock", std::process::id()));
+        let handle = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(Definitions::from_toml(DEFINITIONS).unwrap())
+            .with_env("MIKROS_HEALTH_ADDRESS", &format!("unix:{}", path.display()))
+            .build()
+            .unwrap()
+            .spawn()
+            .await
+            .unwrap();
+
+        let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+        stream
+            .write_all(b"GET /health/live HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200"));
+
+        assert!(handle.shutdown().await.is_ok());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    #[allow(clippy::result_large_err)]
+    async fn test_spawn_with_invalid_custom_settings() {
+        #[derive(serde_derive::Deserialize, validator::Validate)]
+        struct Settings {
+            #[validate(range(min = 1))]
+            max_items: i32,
+        }
+
+        let result = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(Definitions::from_toml(DEFINITIONS).unwrap())
+            .with_definitions_check(|definitions| {
+                definitions.try_custom_settings_validated::<Settings>()?;
+                Ok(())
+            })
+            .build()
+            .unwrap()
+            .spawn()
+            .await;
+
+        let err = result.err().unwrap();
+        assert!(err.to_string().contains("invalid settings at 'service'"));
+    }
+
+    #[derive(Clone)]
+    struct EnvFeature;
+
+    #[async_trait::async_trait]
+    impl plugin::feature::Feature for EnvFeature {
+        fn name(&self) -> &str {
+            "env_feature"
+        }
+
+        fn info(&self) -> Option<serde_json::Value> {
+            None
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn can_be_initialized(&self, _: Arc<Definitions>, _: Arc<Env>) -> merrors::Result<bool> {
+            Ok(true)
+        }
+
+        fn env_variables(&self, _suffix: &str) -> Vec<crate::env::Variable> {
+            ["ENV_FEATURE_TOKEN", "ENV_FEATURE_SECRET"]
+                .iter()
+                .map(|name| crate::env::Variable {
+                    name: name.to_string(),
+                    suffixed_name: None,
+                    default: None,
+                    required: true,
+                    kind: "String".to_string(),
+                    description: None,
+                    ..Default::default()
+                })
+                .collect()
+        }
+
+        async fn initialize(&mut self, _: Arc<context::Context>) -> merrors::Result<()> {
+            Ok(())
+        }
+
+        async fn cleanup(&self) {}
+
+        fn service_api(&self) -> Option<&dyn std::any::Any> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_with_feature_variables() {
+        let result = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(Definitions::from_toml(DEFINITIONS).unwrap())
+            .with_features(vec![Box::new(EnvFeature)])
+            .build()
+            .unwrap()
+            .spawn()
+            .await;
+
+        let err = result.err().unwrap().to_string();
+        assert!(
+            err.contains("ENV_FEATURE_TOKEN: required variable is not set (feature 'env_feature')")
+        );
+        assert!(
+            err.contains(
+                "ENV_FEATURE_SECRET: required variable is not set (feature 'env_feature')"
+            )
+        );
+
+        let handle = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(Definitions::from_toml(DEFINITIONS).unwrap())
+            .with_features(vec![Box::new(EnvFeature)])
+            .with_env("ENV_FEATURE_TOKEN", "abc")
+            .with_env("ENV_FEATURE_SECRET_in_process_service", "xyz")
+            .build()
+            .unwrap()
+            .spawn()
+            .await
+            .unwrap();
+
+        let ctx = handle.context();
+        assert_eq!(
+            ctx.feature_env("env_feature", "ENV_FEATURE_TOKEN"),
+            Some("abc".to_string())
+        );
+        assert_eq!(
+            ctx.feature_env("env_feature", "ENV_FEATURE_SECRET"),
+            Some("xyz".to_string())
+        );
+
+        assert!(handle.shutdown().await.is_ok());
+    }
+
+    #[derive(Clone)]
+    struct HangingFeature(&'static str);
+
+    #[async_trait::async_trait]
+    impl plugin::feature::Feature for HangingFeature {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn info(&self) -> Option<serde_json::Value> {
+            None
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn can_be_initialized(&self, _: Arc<Definitions>, _: Arc<Env>) -> merrors::Result<bool> {
+            Ok(true)
+        }
+
+        async fn initialize(&mut self, _: Arc<context::Context>) -> merrors::Result<()> {
+            Ok(())
+        }
+
+        async fn cleanup(&self) {
+            std::future::pending::<()>().await
+        }
+
+        fn service_api(&self) -> Option<&dyn std::any::Any> {
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_with_hanging_feature() {
+        let definitions =
+            Definitions::from_toml(&format!("{DEFINITIONS}\n[shutdown]\ndrain_timeout = 1\n"))
+                .unwrap();
+
+        let handle = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(definitions)
+            .with_features(vec![
+                Box::new(HangingFeature("hanging_feature")),
+                Box::new(HangingFeature("another_hanging_feature")),
+            ])
+            .build()
+            .unwrap()
+            .spawn()
+            .await
+            .unwrap();
+
+        let started = std::time::Instant::now();
+        let err = handle.shutdown().await.err().unwrap().to_string();
+
+        assert!(err.contains("shutdown timeout exceeded"));
+        assert!(err.contains("feature cleanup (hanging_feature)"));
+        assert!(err.contains("feature cleanup (another_hanging_feature)"));
+
+        // Both features share the same drain budget.
+        assert!(started.elapsed() < std::time::Duration::from_secs(2));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_wait_shutdown_signal_on_sigterm() {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        // Keeps the process from being terminated by the signals sent below.
+        let _sigterm = signal(SignalKind::terminate()).unwrap();
+        let mut task = task::spawn(Service::wait_shutdown_signal());
+
+        // The signal is sent until the task starts listening for it.
+        for _ in 0..50 {
+            std::process::Command::new("kill")
+                .args(["-TERM", &std::process::id().to_string()])
+                .status()
+                .unwrap();
+
+            if let Ok(name) = time::timeout(std::time::Duration::from_millis(100), &mut task).await
+            {
+                assert_eq!(name.unwrap().unwrap(), "SIGTERM");
+                return;
+            }
+        }
+
+        panic!("SIGTERM was not received");
+    }
+
+    #[derive(Clone, Default)]
+    struct SecretFeature {
+        password: Option<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl plugin::feature::Feature for SecretFeature {
+        fn name(&self) -> &str {
+            "secret_feature"
+        }
+
+        fn info(&self) -> Option<serde_json::Value> {
+            None
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn can_be_initialized(&self, _: Arc<Definitions>, _: Arc<Env>) -> merrors::Result<bool> {
+            Ok(true)
+        }
+
+        async fn initialize(&mut self, ctx: Arc<context::Context>) -> merrors::Result<()> {
+            let password = ctx.secret("SECRET_FEATURE_PASSWORD").await?;
+            self.password = Some(password.expose().to_string());
+            Ok(())
+        }
+
+        async fn cleanup(&self) {}
+
+        fn service_api(&self) -> Option<&dyn std::any::Any> {
+            Some(self)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_with_feature_resolving_secret() {
+        let spawn = ServiceBuilder::new()
+            .http(axum::Router::new())
+            .with_definitions(Definitions::from_toml(DEFINITIONS).unwrap())
+            .with_features(vec![Box::new(SecretFeature::default())])
+            .with_env("SECRET_FEATURE_PASSWORD", "admin123")
+            .build()
+            .unwrap()
+            .spawn();
+
+        let handle = time::timeout(std::time::Duration::from_secs(5), spawn)
+            .await
+            .expect("feature initialization deadlocked")
+            .unwrap();
+
+        let feature = handle.context().feature("secret_feature").await.unwrap();
+        let feature = feature
+            .service_api()
+            .and_then(|api| api.downcast_ref::<SecretFeature>())
+            .unwrap();
+
+        assert_eq!(feature.password.as_deref(), Some("admin123"));
+        assert!(handle.shutdown().await.is_ok());
+    }
+}
@@ -2,6 +2,7 @@ use std::any::Any;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Router;
 use futures::lock::Mutex;
@@ -9,6 +10,7 @@ use http::{request::Request, response::Response};
 use tonic::body::BoxBody;
 use tonic::server::NamedService;
 
+use crate::args::Flag;
 use crate::http::ServiceState;
 use crate::service::Service;
 use crate::service::errors::Error;
@@ -24,18 +26,36 @@ use crate::{definition, errors, plugin};
 /// data type.
 pub struct ServiceBuilder {
     pub(crate) servers: HashMap<String, Box<dyn plugin::service::Service>>,
+    grpc: Option<Grpc>,
+    grpc_file_descriptor_sets: Vec<&'static [u8]>,
     pub(crate) features: Vec<Box<dyn plugin::feature::Feature>>,
     pub(crate) custom_service_types: Vec<String>,
     pub(crate) service_options: HashMap<String, serde_json::Value>,
+    pub(crate) definitions: Option<definition::Definitions>,
+    pub(crate) envs: HashMap<String, String>,
+    pub(crate) reload_interval: Option<Duration>,
+    pub(crate) definitions_check: Option<DefinitionsCheck>,
+    pub(crate) flags: Vec<Flag>,
 }
 
+// A custom check executed over the service definitions.
+pub(crate) type DefinitionsCheck =
+    Arc<dyn Fn(&definition::Definitions) -> errors::Result<()> + Send + Sync>;
+
 impl ServiceBuilder {
     pub fn new() -> Self {
         Self {
             servers: HashMap::new(),
+            grpc: None,
+            grpc_file_descriptor_sets: Vec::new(),
             features: Vec::new(),
             custom_service_types: Vec::new(),
             service_options: HashMap::new(),
+            definitions: None,
+            envs: HashMap::new(),
+            reload_interval: None,
+            definitions_check: None,
+            flags: Vec::new(),
         }
     }
 
@@ -93,13 +113,11 @@ impl ServiceBuilder {
     {
         let kind = definition::ServiceKind::Grpc;
 
-        if self.servers.contains_key(&kind.to_string()) {
+        if self.grpc.is_some() {
             Self::abort(Error::ServiceAlreadyInitialized(kind.to_string()).into());
         }
 
-        self.servers
-            .insert(kind.to_string(), Box::new(Grpc::new(server)));
-
+        self.grpc = Some(Grpc::new(server));
         self
     }
 
@@ -121,15 +139,97 @@ impl ServiceBuilder {
     {
         let kind = definition::ServiceKind::Grpc;
 
-        if self.servers.contains_key(&kind.to_string()) {
+        if self.grpc.is_some() {
             Self::abort(Error::ServiceAlreadyInitialized(kind.to_string()).into());
         }
 
-        self.servers.insert(
-            kind.to_string(),
-            Box::new(Grpc::new_with_lifecycle(server, lifecycle)),
-        );
+        self.grpc = Some(Grpc::new_with_lifecycle(server, lifecycle));
+        self
+    }
+
+    /// Adds another gRPC service, implementing its API, to be served by the
+    /// same gRPC server. It can be called any number of times, after the gRPC
+    /// service type is initialized with `grpc` or `grpc_with_lifecycle`. If it
+    /// was not, the gRPC service type is initialized with this service.
+    pub fn with_grpc_service<S>(mut self, server: S) -> Self
+    where
+        S: tonic::codegen::Service<
+                Request<BoxBody>,
+                Response = Response<BoxBody>,
+                Error = Infallible,
+            > + NamedService
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        match self.grpc.as_mut() {
+            None => self.grpc = Some(Grpc::new(server)),
+            Some(grpc) => {
+                if let Err(e) = grpc.add_service(server) {
+                    Self::abort(e);
+                }
+            }
+        }
+
+        self
+    }
 
+    /// Registers an encoded protobuf `FileDescriptorSet` (usually generated by
+    /// `tonic_build` with its `file_descriptor_set_path` option) to be served by
+    /// the gRPC server reflection service. Reflection can be enabled or disabled
+    /// with the `reflection` setting inside the `services.grpc` section of the
+    /// service.toml file. By default, it is enabled except for production
+    /// deployments, according to the `MIKROS_SERVICE_DEPLOY` variable.
+    pub fn with_grpc_file_descriptor_set(mut self, file_descriptor_set: &'static [u8]) -> Self {
+        self.grpc_file_descriptor_sets.push(file_descriptor_set);
+        self
+    }
+
+    /// Uses `definitions` as the service definitions instead of loading them
+    /// from the service.toml file. Command line arguments are not parsed when
+    /// this option is used, allowing the service to be executed in-process,
+    /// like inside integration tests (see `Service::spawn`).
+    pub fn with_definitions(mut self, definitions: definition::Definitions) -> Self {
+        self.definitions = Some(definitions);
+        self
+    }
+
+    /// Sets an environment variable value for the service. Values set this
+    /// way take precedence over the ones from the process environment.
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.envs.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Enables watching the service definitions files, checking them for
+    /// changes at every interval. Changed files are reloaded and, if valid,
+    /// the log level and features settings are applied without restarting
+    /// the service. It has no effect when definitions are set with the
+    /// `with_definitions` API.
+    pub fn with_definitions_reload(mut self, interval: Duration) -> Self {
+        self.reload_interval = Some(interval);
+        self
+    }
+
+    /// Registers a custom command line flag for the service, which is
+    /// displayed by the `--help` option and can be retrieved using the
+    /// `Context::has_flag` and `Context::flag_value` APIs.
+    pub fn with_flag(mut self, flag: Flag) -> Self {
+        self.flags.push(flag);
+        self
+    }
+
+    /// Sets a custom check over the service definitions, executed when the
+    /// service starts and when its definitions are reloaded. It allows failing
+    /// fast with invalid settings, like the ones inside the `[service]`
+    /// section, using the `Definitions::try_custom_settings_validated` API.
+    pub fn with_definitions_check<F>(mut self, check: F) -> Self
+    where
+        F: Fn(&definition::Definitions) -> errors::Result<()> + Send + Sync + 'static,
+    {
+        self.definitions_check = Some(Arc::new(check));
         self
     }
 
@@ -247,7 +347,8 @@ impl ServiceBuilder {
         self
     }
 
-    /// Disables the default health endpoint for HTTP services.
+    /// Disables the default health endpoints (/health, /health/live and
+    /// /health/ready) for HTTP services.
     pub fn without_health_endpoint(mut self) -> Self {
         self.service_options.insert(
             "without_health_endpoint".to_string(),
@@ -257,8 +358,29 @@ impl ServiceBuilder {
         self
     }
 
+    /// Disables the standard gRPC health checking service (grpc.health.v1.Health)
+    /// that is registered by default for gRPC services.
+    pub fn without_grpc_health_service(mut self) -> Self {
+        self.service_options.insert(
+            "without_grpc_health_service".to_string(),
+            serde_json::Value::Bool(true),
+        );
+
+        self
+    }
+
     /// Builds the service to be executed.
-    pub fn build(self) -> errors::Result<Service> {
+    #[allow(clippy::result_large_err)]
+    pub fn build(mut self) -> errors::Result<Service> {
+        if let Some(mut grpc) = self.grpc.take() {
+            for set in self.grpc_file_descriptor_sets.drain(..) {
+                grpc.add_file_descriptor_set(set);
+            }
+
+            self.servers
+                .insert(definition::ServiceKind::Grpc.to_string(), Box::new(grpc));
+        }
+
         match Service::new(self) {
             Ok(svc) => Ok(svc),
             Err(e) => Err(e.into()),
@@ -1,8 +1,15 @@
 use futures::lock::Mutex;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 
-use crate::definition::Definitions;
+use tokio::sync::watch;
+
+use crate::definition::service::Address;
+use crate::definition::{Definitions, ServiceKind};
 use crate::env::Env;
+use crate::health::{self, Health};
+use crate::secrets::{self, Secret};
+use crate::service::builder::DefinitionsCheck;
 use crate::service::errors::Error;
 use crate::{env, errors, logger, plugin};
 
@@ -15,6 +22,12 @@ pub struct Context {
 
     pub(crate) envs: Arc<Env>,
     pub(crate) features: Arc<Mutex<Vec<Box<dyn plugin::feature::Feature>>>>,
+    pub(crate) health: Arc<Health>,
+    bound_addresses: Arc<RwLock<HashMap<String, Address>>>,
+    definitions_tx: Arc<watch::Sender<Arc<Definitions>>>,
+    pub(crate) flags: Arc<HashMap<String, Option<String>>>,
+    pub(crate) secrets: Arc<secrets::Resolver>,
+    feature_envs: Arc<RwLock<HashMap<String, HashMap<String, String>>>>,
 }
 
 impl Context {
@@ -24,11 +37,20 @@ impl Context {
         definitions: Arc<Definitions>,
         features: Vec<Box<dyn plugin::feature::Feature>>,
     ) -> Self {
+        let (definitions_tx, _) = watch::channel(definitions.clone());
+        let secrets = Arc::new(secrets::Resolver::new(&definitions, HashMap::new()));
+
         Self {
             logger,
             envs,
             definitions,
             features: Arc::new(Mutex::new(features)),
+            health: Arc::new(Health::new()),
+            bound_addresses: Arc::new(RwLock::new(HashMap::new())),
+            definitions_tx: Arc::new(definitions_tx),
+            flags: Arc::new(HashMap::new()),
+            secrets,
+            feature_envs: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -54,6 +76,19 @@ impl Context {
         &self.envs
     }
 
+    /// Returns the value of an environment variable declared by a feature
+    /// through the `Feature::env_variables` API. Values are loaded when the
+    /// feature is initialized, considering its service-specific (suffixed)
+    /// variable and its default value.
+    pub fn feature_env(&self, feature: &str, name: &str) -> Option<String> {
+        self.feature_envs
+            .read()
+            .unwrap()
+            .get(feature)
+            .and_then(|envs| envs.get(name))
+            .cloned()
+    }
+
     /// Gives the service access to the service definitions loaded from the
     /// service.toml file.
     pub fn definitions(&self) -> Arc<Definitions> {
@@ -66,6 +101,50 @@ impl Context {
         &self.definitions
     }
 
+    /// Subscribes to changes of the service definitions, which only happen
+    /// when the service is built with the `ServiceBuilder::with_definitions_reload`
+    /// API. The receiver always holds the most recent valid definitions, while
+    /// the `definitions` API keeps returning the ones loaded at startup.
+    pub fn subscribe_definitions(&self) -> watch::Receiver<Arc<Definitions>> {
+        self.definitions_tx.subscribe()
+    }
+
+    /// Returns if a custom command line flag, registered with the
+    /// `ServiceBuilder::with_flag` API, was used.
+    pub fn has_flag(&self, name: &str) -> bool {
+        self.flags.contains_key(name)
+    }
+
+    /// Returns the value of a custom command line flag that requires a value.
+    pub fn flag_value(&self, name: &str) -> Option<String> {
+        self.flags.get(name).cloned().flatten()
+    }
+
+    /// Retrieves a secret using the configured providers, which can read
+    /// mounted files, environment variables or use providers implemented by
+    /// features.
+    pub async fn secret(&self, name: &str) -> errors::Result<Secret> {
+        let providers = self
+            .features
+            .lock()
+            .await
+            .iter()
+            .filter(|f| f.is_enabled())
+            .filter_map(|f| f.secrets_provider())
+            .collect();
+
+        match self.secrets.resolve(name, providers).await? {
+            Some(secret) => Ok(secret),
+            None => {
+                let error = secrets::errors::Error::SecretNotFound(name.to_string());
+                Err(errors::ServiceError::from_error(
+                    self.clone().into(),
+                    error.into(),
+                ))
+            }
+        }
+    }
+
     /// Returns the current service name.
     #[must_use]
     pub fn service_name(&self) -> String {
@@ -86,6 +165,25 @@ impl Context {
         }
     }
 
+    /// Returns the address where a service kind is really listening for
+    /// connections, which is useful to discover the port assigned by the
+    /// system when the service is configured to use port 0. It is available
+    /// after the service is initialized.
+    pub fn bound_address(&self, kind: &ServiceKind) -> Option<Address> {
+        self.bound_addresses
+            .read()
+            .unwrap()
+            .get(&kind.to_string())
+            .cloned()
+    }
+
+    pub(crate) fn set_bound_address(&self, kind: &ServiceKind, address: Address) {
+        self.bound_addresses
+            .write()
+            .unwrap()
+            .insert(kind.to_string(), address);
+    }
+
     /// On success, returns the feature found inside the context.
     pub async fn feature(&self, name: &str) -> errors::Result<Box<dyn plugin::feature::Feature>> {
         match self
@@ -117,26 +215,190 @@ impl Context {
         }
     }
 
-    pub(crate) async fn initialize_features(&mut self) -> errors::Result<()> {
-        for feature in self.features.lock().await.iter_mut() {
-            if feature.can_be_initialized(self.definitions.clone(), self.envs.clone())? {
-                feature.initialize(self.clone().into()).await?;
+    /// Returns the service liveness probe result. It only fails if some of
+    /// the service tasks has failed.
+    pub fn liveness(&self) -> health::Report {
+        self.health.liveness()
+    }
+
+    /// Returns the service readiness probe result. It only succeeds when the
+    /// service is running and all its features and service implementations
+    /// report themselves as healthy.
+    pub async fn readiness(&self) -> health::Report {
+        self.health.readiness(self.clone().into()).await
+    }
+
+    // Executes all checks over definitions: the features ones and, if set,
+    // the custom one from the service.
+    pub(crate) async fn check_definitions(
+        &self,
+        definitions: &Definitions,
+        check: Option<&DefinitionsCheck>,
+    ) -> errors::Result<()> {
+        for feature in self.features.lock().await.iter() {
+            feature.validate_definitions(definitions)?;
+        }
+
+        if let Some(check) = check {
+            check(definitions)?;
+        }
+
+        Ok(())
+    }
+
+    // Initializes every feature that can be initialized. Their declared
+    // environment variables are loaded first, so all missing ones, from all
+    // features, are reported at once.
+    pub(crate) async fn initialize_features(
+        &mut self,
+        source: &dyn Fn(&str) -> Option<String>,
+    ) -> errors::Result<()> {
+        let suffix = self.definitions.name.to_snake_case();
+        let mut initialize = Vec::new();
+        let mut invalid = Vec::new();
+
+        // Features are initialized through clones, outside the lock, so they
+        // can use the context APIs that also access them, like secrets.
+        let features = self.features.lock().await.clone();
+
+        for feature in features {
+            let can_be_initialized =
+                feature.can_be_initialized(self.definitions.clone(), self.envs.clone())?;
+
+            if can_be_initialized {
+                let variables = feature.env_variables(&suffix);
+                match Env::load_variables(&variables, &suffix, source) {
+                    Ok(values) => {
+                        self.feature_envs
+                            .write()
+                            .unwrap()
+                            .insert(feature.name().to_string(), values);
+                    }
+                    Err(errors) => invalid.extend(
+                        errors
+                            .iter()
+                            .map(|e| format!("{e} (feature '{}')", feature.name())),
+                    ),
+                }
+            }
+
+            if can_be_initialized {
+                initialize.push(feature);
+            }
+        }
+
+        if !invalid.is_empty() {
+            let error = Error::InvalidFeatureVariables(invalid.join(", "));
+            return Err(errors::ServiceError::from_error(
+                self.clone().into(),
+                error.into(),
+            ));
+        }
+
+        for mut feature in initialize {
+            let name = Health::feature_name(feature.name());
+            self.health.set_component(&name, health::Status::Starting);
+
+            if let Err(e) = feature.initialize(self.clone().into()).await {
+                self.health.set_component_failure(&name, &e.to_string());
+                return Err(e);
             }
+
+            self.replace_feature(feature).await;
+            self.health.set_component(&name, health::Status::Up);
         }
 
         Ok(())
     }
 
-    pub(crate) async fn cleanup_features(&mut self) {
-        for feature in self.features.lock().await.iter() {
-            if feature.is_enabled() {
-                feature.cleanup().await;
+    // Applies the safe sections of reloaded definitions, i.e., the log level
+    // and features settings, and notifies subscribers about them.
+    pub(crate) async fn apply_definitions(&self, definitions: Arc<Definitions>) {
+        let previous = self.definitions_tx.borrow().clone();
+
+        if let Some(level) = definitions.log().level {
+            if previous.log().level.as_ref() != Some(&level) {
+                // Unknown levels fall back to info, like when the service
+                // starts.
+                let level = level.parse::<logger::Level>().unwrap_or_else(|e| {
+                    self.logger.warningf(
+                        "using info log level",
+                        serde_json::json!({ "error": e, "level": level }),
+                    );
+                    logger::Level::Info
+                });
+
+                self.logger.change_level(level);
             }
         }
+
+        // Features are updated through clones, outside the lock, so they can
+        // use the context APIs that also access them.
+        let changed: Vec<Box<dyn plugin::feature::Feature>> = self
+            .features
+            .lock()
+            .await
+            .iter()
+            .filter(|f| previous.feature(f.name()) != definitions.feature(f.name()))
+            .cloned()
+            .collect();
+
+        for mut feature in changed {
+            let name = feature.name().to_string();
+
+            if let Err(e) = feature
+                .on_definitions_changed(self.clone().into(), definitions.clone())
+                .await
+            {
+                self.logger.errorf(
+                    "feature could not apply new definitions",
+                    serde_json::json!({
+                        "feature.name": name,
+                        "error": e.to_string(),
+                    }),
+                );
+
+                continue;
+            }
+
+            self.replace_feature(feature).await;
+        }
+
+        self.definitions_tx.send_replace(definitions);
+    }
+
+    // Puts back a feature that was updated outside the features lock.
+    async fn replace_feature(&self, feature: Box<dyn plugin::feature::Feature>) {
+        let mut features = self.features.lock().await;
+        if let Some(current) = features.iter_mut().find(|f| f.name() == feature.name()) {
+            *current = feature;
+        }
+    }
+
+    // Releases resources from all enabled features, which must finish before
+    // the shutdown `deadline`. Returns the name of the features that didn't
+    // finish in time.
+    pub(crate) async fn cleanup_features(&mut self, deadline: tokio::time::Instant) -> Vec<String> {
+        let mut hung = Vec::new();
+
+        // Features are not Sync, so they are borrowed mutably to keep the
+        // future Send.
+        for feature in self.features.lock().await.iter_mut() {
+            if feature.is_enabled()
+                && tokio::time::timeout_at(deadline, feature.cleanup())
+                    .await
+                    .is_err()
+            {
+                hung.push(feature.name().to_string());
+            }
+        }
+
+        hung
     }
 }
 
 /// Retrieves the mikros Context from an RPC request argument.
+#[allow(clippy::result_large_err)]
 pub fn from_request<B>(request: &tonic::Request<B>) -> Result<Arc<Context>, tonic::Status>
 where
     B: prost::Message,
@@ -148,6 +410,15 @@ where
 }
 
 /// A macro to help service coupling using gRPC connections.
+///
+/// By default, the created client uses a `tonic::transport::Channel`. When
+/// called with `with_destination` as its last argument, the client uses a
+/// `mikros::grpc::Channel` instead, so errors received from it have the
+/// client name as their destination.
+///
+/// Destination propagation is not the default because it changes the client
+/// type, which would break applications that store the clients created by
+/// this macro, like `Option<GreeterClient<tonic::transport::Channel>>`.
 #[macro_export]
 macro_rules! link_grpc_service {
     ($context:ident, $client:ident, $client_name:expr) => {{
@@ -162,4 +433,16 @@ macro_rules! link_grpc_service {
             }
         }
     }};
+    ($context:ident, $client:ident, $client_name:expr, with_destination) => {{
+        let url = $context.client_connection_url($client_name);
+        match mikros::grpc::connect(url, $client_name).await {
+            Ok(channel) => $client::new(channel),
+            Err(e) => {
+                return Err(mikros::errors::ServiceError::custom(
+                    $context,
+                    &e.to_string(),
+                ));
+            }
+        }
+    }};
 }
@@ -9,6 +9,12 @@ crate::module_errors!(
         ServiceKindUninitialized(k: definition::ServiceKind) => "service type uninitialized: {}",
         FeatureDisabled(f: String) => "feature '{}' is disabled",
         ServiceAlreadyInitialized(k: String) => "service '{}' already initialized",
-        ServiceNotFound(s: String) => "service '{}' implementation not found"
+        ServiceNotFound(s: String) => "service '{}' implementation not found",
+        ShutdownTimeout(s: String) => "shutdown timeout exceeded while finishing: {}",
+        SignalListener(s: String, e: String) => "could not listen for {}: {}",
+        InvalidAddress(k: String, e: String) => "invalid '{}' service address: {}",
+        UnsupportedAddress(k: String) => "service '{}' does not support listening addresses",
+        BindFailure(a: String, e: String) => "could not bind to '{}': {}",
+        InvalidFeatureVariables(v: String) => "invalid feature environment variables: {}"
     }
 );
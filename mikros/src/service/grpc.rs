@@ -2,70 +2,193 @@ mod errors;
 
 use std::collections::HashMap;
 use std::convert::Infallible;
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 
 use futures::lock::Mutex;
 use http::{request::Request, response::Response};
 use tokio::sync::watch;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
 use tonic::body::BoxBody;
 use tonic::server::NamedService;
-use tonic::transport::Server;
+use tonic::service::Routes;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::{Server, ServerTlsConfig};
+use tonic_health::ServingStatus;
+use tonic_health::server::HealthReporter;
 
+use crate::definition::service::Address;
+use crate::errors as merrors;
 use crate::grpc;
 use crate::service::context::Context;
 use crate::service::lifecycle::Lifecycle;
-use crate::{definition, env, plugin};
-use crate::{env_is_default, errors as merrors};
+use crate::service::listener::{self, BoundListener, Incoming};
+use crate::{definition, env, plugin, tls};
 
 #[derive(Clone)]
-pub(crate) struct Grpc<S> {
-    port: i32,
-    server: S,
+pub(crate) struct Grpc {
+    address: Address,
+    listener: BoundListener,
+    routes: Routes,
+    services: Vec<&'static str>,
+    health_reporter: Option<HealthReporter>,
+    file_descriptor_sets: Vec<&'static [u8]>,
+    reflection: bool,
+    tls: Option<ServerTlsConfig>,
+    client_auth: &'static str,
     lifecycle: Option<Arc<Mutex<dyn Lifecycle>>>,
 }
 
-impl<S> Grpc<S>
-where
-    S: tonic::codegen::Service<Request<BoxBody>, Response = Response<BoxBody>, Error = Infallible>
-        + NamedService
-        + Clone
-        + Send
-        + Sync
-        + 'static,
-    S::Future: Send + 'static,
-{
-    pub(crate) fn new_with_lifecycle<L: Lifecycle + 'static>(
-        server: S,
-        lifecycle: Arc<Mutex<L>>,
-    ) -> Self {
-        Self {
-            port: 0,
-            server,
-            lifecycle: Some(lifecycle),
-        }
+// Settings that can be set for the service inside the 'services.grpc' section
+// of the service.toml file.
+#[derive(serde_derive::Deserialize, Default)]
+struct Definitions {
+    reflection: Option<bool>,
+    tls: Option<tls::Settings>,
+}
+
+impl Grpc {
+    pub(crate) fn new_with_lifecycle<S, L>(server: S, lifecycle: Arc<Mutex<L>>) -> Self
+    where
+        S: tonic::codegen::Service<
+                Request<BoxBody>,
+                Response = Response<BoxBody>,
+                Error = Infallible,
+            > + NamedService
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+        L: Lifecycle + 'static,
+    {
+        let mut s = Self::new(server);
+        s.lifecycle = Some(lifecycle);
+        s
     }
 
-    pub(crate) fn new(server: S) -> Self {
+    pub(crate) fn new<S>(server: S) -> Self
+    where
+        S: tonic::codegen::Service<
+                Request<BoxBody>,
+                Response = Response<BoxBody>,
+                Error = Infallible,
+            > + NamedService
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+    {
         Self {
-            port: 0,
-            server,
+            address: Address::Port(0),
+            listener: BoundListener::default(),
+            routes: Routes::new(server),
+            services: vec![S::NAME],
+            health_reporter: None,
+            file_descriptor_sets: Vec::new(),
+            reflection: false,
+            tls: None,
+            client_auth: "none",
             lifecycle: None,
         }
     }
+
+    // Adds another service to be served by the same gRPC server.
+    pub(crate) fn add_service<S>(&mut self, server: S) -> Result<(), merrors::Error>
+    where
+        S: tonic::codegen::Service<
+                Request<BoxBody>,
+                Response = Response<BoxBody>,
+                Error = Infallible,
+            > + NamedService
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        if self.services.contains(&S::NAME) {
+            return Err(errors::Error::ServiceAlreadyAdded(S::NAME.to_string()).into());
+        }
+
+        self.routes = std::mem::take(&mut self.routes).add_service(server);
+        self.services.push(S::NAME);
+        Ok(())
+    }
+
+    // Adds an encoded protobuf FileDescriptorSet to be used by the reflection
+    // service.
+    pub(crate) fn add_file_descriptor_set(&mut self, file_descriptor_set: &'static [u8]) {
+        self.file_descriptor_sets.push(file_descriptor_set);
+    }
+
+    // Adds the gRPC server reflection services (v1 and v1alpha) into the server
+    // routes, using the registered file descriptor sets.
+    fn add_reflection_service(&mut self) -> Result<(), errors::Error> {
+        let v1 = self
+            .reflection_builder()
+            .build_v1()
+            .map_err(|e| errors::Error::ReflectionFailure(e.to_string()))?;
+
+        let v1alpha = self
+            .reflection_builder()
+            .build_v1alpha()
+            .map_err(|e| errors::Error::ReflectionFailure(e.to_string()))?;
+
+        self.routes = std::mem::take(&mut self.routes)
+            .add_service(v1)
+            .add_service(v1alpha);
+
+        self.reflection = true;
+        Ok(())
+    }
+
+    // Loads the server TLS configuration, if TLS was enabled for the service.
+    fn load_tls(
+        &mut self,
+        settings: Option<tls::Settings>,
+        envs: &env::Env,
+    ) -> Result<(), merrors::Error> {
+        if let Some(config) = tls::Config::load(&definition::ServiceKind::Grpc, settings, envs)? {
+            self.tls = Some(config.grpc_config()?);
+            self.client_auth = config.client_auth();
+        }
+
+        Ok(())
+    }
+
+    fn reflection_builder(&self) -> tonic_reflection::server::Builder<'static> {
+        self.file_descriptor_sets.iter().fold(
+            tonic_reflection::server::Builder::configure(),
+            |builder, set| builder.register_encoded_file_descriptor_set(set),
+        )
+    }
+
+    // Adds the standard gRPC health service into the server routes.
+    fn add_health_service(&mut self) {
+        let (reporter, health_server) = tonic_health::server::health_reporter();
+
+        self.routes = std::mem::take(&mut self.routes).add_service(health_server);
+        self.health_reporter = Some(reporter);
+    }
+
+    // Updates the status of the services (and of the server) reported by the
+    // gRPC health service.
+    async fn set_serving_status(&self, status: ServingStatus) {
+        if let Some(reporter) = &self.health_reporter {
+            let mut reporter = reporter.clone();
+            reporter.set_service_status("", status).await;
+
+            for name in &self.services {
+                reporter.set_service_status(name, status).await;
+            }
+        }
+    }
 }
 
 #[async_trait::async_trait]
-impl<S> Lifecycle for Grpc<S>
-where
-    S: tonic::codegen::Service<Request<BoxBody>, Response = Response<BoxBody>, Error = Infallible>
-        + NamedService
-        + Clone
-        + Send
-        + Sync
-        + 'static,
-    S::Future: 'static + Send,
-{
+impl Lifecycle for Grpc {
     async fn on_start(&mut self, ctx: Arc<Context>) -> merrors::Result<()> {
         if let Some(lifecycle) = &self.lifecycle {
             return lifecycle.lock().await.on_start(ctx).await;
@@ -84,16 +207,7 @@ where
 }
 
 #[async_trait::async_trait]
-impl<S> plugin::service::Service for Grpc<S>
-where
-    S: tonic::codegen::Service<Request<BoxBody>, Response = Response<BoxBody>, Error = Infallible>
-        + NamedService
-        + Clone
-        + Send
-        + Sync
-        + 'static,
-    S::Future: Send + 'static,
-{
+impl plugin::service::Service for Grpc {
     fn kind(&self) -> definition::ServiceKind {
         definition::ServiceKind::Grpc
     }
@@ -103,21 +217,45 @@ where
         ctx: Arc<Context>,
         definitions: Arc<definition::Definitions>,
         envs: Arc<env::Env>,
-        _: HashMap<String, serde_json::Value>,
+        options: HashMap<String, serde_json::Value>,
     ) -> merrors::Result<()> {
-        match definitions.get_service_type(definition::ServiceKind::Grpc) {
-            Err(e) => return Err(merrors::ServiceError::from_error(ctx.clone(), e.into())),
-            Ok(service_type) => {
-                self.port = match service_type.1 {
-                    None => envs.grpc_port,
-                    Some(port) => {
-                        if !env_is_default!(envs, grpc_port) {
-                            envs.grpc_port
-                        } else {
-                            port
-                        }
-                    }
-                }
+        let kind = definition::ServiceKind::Grpc;
+        match listener::resolve_address(kind.clone(), &definitions, &envs)
+            .and_then(|address| self.listener.bind(&ctx, &kind, &address))
+        {
+            Err(e) => return Err(merrors::ServiceError::from_error(ctx.clone(), e)),
+            Ok(address) => self.address = address,
+        }
+
+        // Register the standard health service if it was not disabled.
+        let without_health_service = options
+            .get("without_grpc_health_service")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
+
+        if !without_health_service && self.health_reporter.is_none() {
+            self.add_health_service();
+        }
+
+        // Server reflection is enabled by default, except for production
+        // deployments, where enabling it is warned about.
+        let settings: Definitions = definitions
+            .load_service(definition::ServiceKind::Grpc)
+            .unwrap_or_default();
+
+        if let Err(e) = self.load_tls(settings.tls.clone(), &envs) {
+            return Err(merrors::ServiceError::from_error(ctx.clone(), e));
+        }
+
+        let reflection = settings.reflection.unwrap_or(!envs.is_production());
+        if reflection && !self.reflection {
+            if envs.is_production() {
+                ctx.logger_ref()
+                    .warning("gRPC server reflection is enabled in a production deployment");
+            }
+
+            if let Err(e) = self.add_reflection_service() {
+                return Err(merrors::ServiceError::from_error(ctx.clone(), e.into()));
             }
         }
 
@@ -126,8 +264,14 @@ where
 
     fn info(&self) -> serde_json::Value {
         serde_json::json!({
-            "svc.port": self.port,
+            "svc.port": self.address.port(),
+            "svc.address": self.address.to_string(),
             "svc.mode": definition::ServiceKind::Grpc.to_string(),
+            "svc.services": self.services,
+            "svc.health_service": self.health_reporter.is_some(),
+            "svc.reflection": self.reflection,
+            "svc.tls": self.tls.is_some(),
+            "svc.client_auth": self.client_auth,
         })
     }
 
@@ -140,8 +284,6 @@ where
         ctx: Arc<Context>,
         shutdown_rx: watch::Receiver<()>,
     ) -> merrors::Result<()> {
-        let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), self.port as u16);
-
         let shutdown_signal = async {
             let mut shutdown_rx = shutdown_rx.clone();
 
@@ -153,12 +295,48 @@ where
             .layer(grpc::ContextExtractor::new(ctx.clone()))
             .into_inner();
 
-        if let Err(e) = Server::builder()
-            .layer(layer)
-            .add_service(self.server.clone())
-            .serve_with_shutdown(addr, shutdown_signal)
-            .await
-        {
+        let mut server = Server::builder();
+        if let Some(tls) = &self.tls {
+            server = match server.tls_config(tls.clone()) {
+                Ok(server) => server,
+                Err(e) => {
+                    let grpc_error = errors::Error::TransportInitFailure(e.to_string());
+                    return Err(merrors::ServiceError::from_error(
+                        ctx.clone(),
+                        grpc_error.into(),
+                    ));
+                }
+            };
+        }
+
+        let router = server.layer(layer).add_routes(self.routes.clone());
+        let result = match self.listener.incoming(&self.address) {
+            Err(e) => Err(e.to_string()),
+            Ok(Incoming::Tcp(listener)) => match TcpIncoming::from_listener(listener, true, None) {
+                Err(e) => Err(e.to_string()),
+                Ok(incoming) => {
+                    self.set_serving_status(ServingStatus::Serving).await;
+                    router
+                        .serve_with_incoming_shutdown(incoming, shutdown_signal)
+                        .await
+                        .map_err(|e| e.to_string())
+                }
+            },
+
+            #[cfg(unix)]
+            Ok(Incoming::Unix(listener)) => {
+                self.set_serving_status(ServingStatus::Serving).await;
+                router
+                    .serve_with_incoming_shutdown(
+                        UnixListenerStream::new(listener),
+                        shutdown_signal,
+                    )
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        };
+
+        if let Err(e) = result {
             let grpc_error = errors::Error::TransportInitFailure(e.to_string());
 
             return Err(merrors::ServiceError::internal(
@@ -171,6 +349,148 @@ where
     }
 
     async fn stop(&self, _: Arc<Context>) {
-        // noop
+        // Let clients know that we are going away before the server stops.
+        self.set_serving_status(ServingStatus::NotServing).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::task::{Context as TaskContext, Poll};
+
+    use super::*;
+    use crate::logger::builder::LoggerBuilder;
+    use crate::plugin::service::Service;
+    use mikros_tests::common::assets_path;
+
+    // Declares an empty gRPC service with the given name.
+    macro_rules! grpc_service {
+        ($name:ident, $service_name:expr) => {
+            #[derive(Clone)]
+            struct $name;
+
+            impl NamedService for $name {
+                const NAME: &'static str = $service_name;
+            }
+
+            impl tonic::codegen::Service<Request<BoxBody>> for $name {
+                type Response = Response<BoxBody>;
+                type Error = Infallible;
+                type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+                fn poll_ready(&mut self, _: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+                    Poll::Ready(Ok(()))
+                }
+
+                fn call(&mut self, _: Request<BoxBody>) -> Self::Future {
+                    std::future::ready(Ok(Response::new(tonic::body::empty_body())))
+                }
+            }
+        };
+    }
+
+    grpc_service!(Greeter, "helloworld.Greeter");
+    grpc_service!(Admin, "helloworld.Admin");
+
+    fn build_context() -> (Arc<Context>, Arc<definition::Definitions>, Arc<env::Env>) {
+        build_context_from("service.toml.ok_ephemeral")
+    }
+
+    fn build_context_from(
+        name: &str,
+    ) -> (Arc<Context>, Arc<definition::Definitions>, Arc<env::Env>) {
+        let filename = assets_path().join("definitions").join(name);
+        let defs = definition::Definitions::new(filename.to_str(), None).unwrap();
+        let envs = env::Env::load(&defs).unwrap();
+        let logger = Arc::new(LoggerBuilder::new().build());
+        let ctx = Arc::new(Context::new(envs.clone(), logger, defs.clone(), vec![]));
+
+        (ctx, defs, envs)
+    }
+
+    #[test]
+    fn test_health_service_enabled_by_default() {
+        let (ctx, defs, envs) = build_context();
+        let mut grpc = Grpc::new(Greeter);
+
+        assert!(grpc.initialize(ctx, defs, envs, HashMap::new()).is_ok());
+        assert!(grpc.health_reporter.is_some());
+        assert_eq!(grpc.info()["svc.health_service"], true);
+    }
+
+    #[test]
+    fn test_without_health_service() {
+        let (ctx, defs, envs) = build_context();
+        let mut grpc = Grpc::new(Greeter);
+        let options = HashMap::from([(
+            "without_grpc_health_service".to_string(),
+            serde_json::Value::Bool(true),
+        )]);
+
+        assert!(grpc.initialize(ctx, defs, envs, options).is_ok());
+        assert!(grpc.health_reporter.is_none());
+        assert_eq!(grpc.info()["svc.health_service"], false);
+    }
+
+    #[test]
+    fn test_multiple_services() {
+        let (ctx, defs, envs) = build_context();
+        let mut grpc = Grpc::new(Greeter);
+
+        assert!(grpc.add_service(Admin).is_ok());
+        assert!(grpc.add_service(Admin).is_err());
+        assert!(grpc.initialize(ctx, defs, envs, HashMap::new()).is_ok());
+        assert_eq!(
+            grpc.info()["svc.services"],
+            serde_json::json!(["helloworld.Greeter", "helloworld.Admin"])
+        );
+    }
+
+    #[test]
+    fn test_reflection_enabled_by_default() {
+        let (ctx, defs, envs) = build_context();
+        let mut grpc = Grpc::new(Greeter);
+        grpc.add_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET);
+
+        assert!(grpc.initialize(ctx, defs, envs, HashMap::new()).is_ok());
+        assert_eq!(grpc.info()["svc.reflection"], true);
+    }
+
+    #[test]
+    fn test_reflection_disabled_in_production() {
+        let (ctx, defs, _) = build_context();
+        let source = |name: &str| (name == "MIKROS_SERVICE_DEPLOY").then(|| "prod".to_string());
+        let envs = env::Env::load_from_source(&defs, &source).unwrap();
+        let mut grpc = Grpc::new(Greeter);
+        grpc.add_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET);
+
+        assert!(grpc.initialize(ctx, defs, envs, HashMap::new()).is_ok());
+        assert_eq!(grpc.info()["svc.reflection"], false);
+    }
+
+    #[test]
+    fn test_reflection_disabled_by_settings() {
+        let (ctx, defs, envs) = build_context_from("service.toml.ok_grpc_reflection");
+        let mut grpc = Grpc::new(Greeter);
+        grpc.add_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET);
+
+        assert!(grpc.initialize(ctx, defs, envs, HashMap::new()).is_ok());
+        assert_eq!(grpc.info()["svc.reflection"], false);
+    }
+
+    #[test]
+    fn test_ephemeral_port() {
+        let (ctx, defs, envs) = build_context();
+        let mut grpc = Grpc::new(Greeter);
+
+        assert!(
+            grpc.initialize(ctx.clone(), defs, envs, HashMap::new())
+                .is_ok()
+        );
+
+        let address = ctx.bound_address(&definition::ServiceKind::Grpc).unwrap();
+        assert_ne!(address.port(), Some(0));
+        assert_eq!(grpc.info()["svc.port"], address.port().unwrap());
+        assert_eq!(grpc.info()["svc.address"], address.to_string());
     }
 }
@@ -1,6 +1,8 @@
 // Module internal error
 crate::module_errors!(
     Error{
-        TransportInitFailure(s: String) => "could not initialize transport layer: {}"
+        TransportInitFailure(s: String) => "could not initialize transport layer: {}",
+        ServiceAlreadyAdded(s: String) => "gRPC service '{}' already added",
+        ReflectionFailure(s: String) => "could not initialize server reflection: {}"
     }
 );
@@ -1,36 +1,53 @@
 mod errors;
-mod health;
+pub(crate) mod health;
+mod tls;
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::fmt::Debug;
+use std::future::Future;
 use std::sync::Arc;
 
 use axum::Router;
-use axum::routing::get;
 use futures::lock::Mutex;
-use tokio::net::TcpListener;
 use tokio::sync::watch::Receiver;
+use tokio_rustls::rustls::ServerConfig;
 
+use crate::definition::service::Address;
 use crate::http::ServiceState;
 use crate::plugin::service::ServiceExecutionMode;
 use crate::service::context::Context;
 use crate::service::lifecycle::Lifecycle;
-use crate::{definition, env, env_is_default, errors as merrors, plugin};
+use crate::service::listener::{self, BoundListener, Incoming};
+use crate::{definition, env, errors as merrors, plugin};
 
 #[derive(Clone)]
 pub(crate) struct Http {
-    port: i32,
+    address: Address,
+    listener: BoundListener,
     internal_health_handler: bool,
+    tls: Option<Arc<ServerConfig>>,
+    client_auth: &'static str,
     router: Router<Arc<Mutex<ServiceState>>>,
     lifecycle: Option<Arc<Mutex<dyn Lifecycle>>>,
     app_state: Option<Arc<Mutex<dyn Any + Send + Sync>>>,
 }
 
+// Settings that can be set for the service inside the 'services.http' section
+// of the service.toml file.
+#[derive(serde_derive::Deserialize, Default)]
+struct Definitions {
+    tls: Option<crate::tls::Settings>,
+}
+
 impl Http {
     pub fn new(router: Router<Arc<Mutex<ServiceState>>>) -> Self {
         Self {
-            port: 0,
+            address: Address::Port(0),
+            listener: BoundListener::default(),
             internal_health_handler: true,
+            tls: None,
+            client_auth: "none",
             router,
             lifecycle: None,
             app_state: None,
@@ -72,6 +89,79 @@ impl Http {
         s
     }
 
+    // Loads the server TLS configuration, if TLS was enabled for the service.
+    fn load_tls(
+        &mut self,
+        definitions: &definition::Definitions,
+        envs: &env::Env,
+    ) -> Result<(), merrors::Error> {
+        let settings: Definitions = definitions
+            .load_service(definition::ServiceKind::Http)
+            .unwrap_or_default();
+
+        if let Some(config) =
+            crate::tls::Config::load(&definition::ServiceKind::Http, settings.tls, envs)?
+        {
+            self.tls = Some(config.server_config()?);
+            self.client_auth = config.client_auth();
+        }
+
+        Ok(())
+    }
+
+    // Serves the application router with connections accepted by the
+    // listener, terminating TLS if it is enabled.
+    async fn serve<L, F>(
+        &self,
+        ctx: Arc<Context>,
+        incoming: L,
+        shutdown_signal: F,
+    ) -> merrors::Result<()>
+    where
+        L: axum::serve::Listener,
+        L::Addr: Clone + Debug + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let router = self.router(ctx.clone());
+        let result = match &self.tls {
+            None => {
+                axum::serve(incoming, router)
+                    .with_graceful_shutdown(shutdown_signal)
+                    .await
+            }
+            Some(config) => {
+                let listener = match tls::TlsListener::new(incoming, config.clone()) {
+                    Ok(listener) => listener,
+                    Err(e) => {
+                        let http_error = errors::Error::InitFailure(e.to_string());
+                        return Err(merrors::ServiceError::from_error(
+                            ctx.clone(),
+                            http_error.into(),
+                        ));
+                    }
+                };
+
+                let router = router.layer(axum::middleware::from_fn(tls::peer_identity::<L::Addr>));
+                axum::serve(
+                    listener,
+                    router.into_make_service_with_connect_info::<tls::TlsConnectInfo<L::Addr>>(),
+                )
+                .with_graceful_shutdown(shutdown_signal)
+                .await
+            }
+        };
+
+        if let Err(e) = result {
+            let http_error = errors::Error::ShutdownFailure(e.to_string());
+            return Err(merrors::ServiceError::from_error(
+                ctx.clone(),
+                http_error.into(),
+            ));
+        }
+
+        Ok(())
+    }
+
     // Builds the application router according user builder options.
     fn router(&self, ctx: Arc<Context>) -> Router {
         let state = match &self.app_state {
@@ -83,7 +173,7 @@ impl Http {
         let mut router = Router::new();
 
         if self.internal_health_handler {
-            router = router.route("/health", get(health::handler));
+            router = router.merge(health::router());
         }
 
         router
@@ -119,8 +209,11 @@ impl plugin::service::Service for Http {
 
     fn info(&self) -> serde_json::Value {
         serde_json::json!({
-            "svc.port": self.port,
+            "svc.port": self.address.port(),
+            "svc.address": self.address.to_string(),
             "svc.mode": definition::ServiceKind::Http.to_string(),
+            "svc.tls": self.tls.is_some(),
+            "svc.client_auth": self.client_auth,
         })
     }
 
@@ -135,21 +228,13 @@ impl plugin::service::Service for Http {
         envs: Arc<env::Env>,
         options: HashMap<String, serde_json::Value>,
     ) -> merrors::Result<()> {
-        // Store the service port to listen for.
-        match definitions.get_service_type(definition::ServiceKind::Http) {
-            Err(e) => return Err(merrors::ServiceError::from_error(ctx.clone(), e.into())),
-            Ok(service_type) => {
-                self.port = match service_type.1 {
-                    None => envs.http_port,
-                    Some(port) => {
-                        if !env_is_default!(envs, http_port) {
-                            envs.http_port
-                        } else {
-                            port
-                        }
-                    }
-                }
-            }
+        // Bind the service address, keeping the real one to listen for.
+        let kind = definition::ServiceKind::Http;
+        match listener::resolve_address(kind.clone(), &definitions, &envs)
+            .and_then(|address| self.listener.bind(&ctx, &kind, &address))
+        {
+            Err(e) => return Err(merrors::ServiceError::from_error(ctx.clone(), e)),
+            Ok(address) => self.address = address,
         }
 
         // Store if we're going to use the default health handler or not.
@@ -157,11 +242,14 @@ impl plugin::service::Service for Http {
             self.internal_health_handler = !health_endpoint.as_bool().unwrap_or(false);
         }
 
+        if let Err(e) = self.load_tls(&definitions, &envs) {
+            return Err(merrors::ServiceError::from_error(ctx.clone(), e));
+        }
+
         Ok(())
     }
 
     async fn run(&mut self, ctx: Arc<Context>, shutdown_rx: Receiver<()>) -> merrors::Result<()> {
-        let addr = format!("0.0.0.0:{}", self.port);
         let shutdown_signal = async move {
             let mut shutdown_rx = shutdown_rx.clone();
 
@@ -169,7 +257,7 @@ impl plugin::service::Service for Http {
             shutdown_rx.changed().await.ok();
         };
 
-        match TcpListener::bind(addr).await {
+        match self.listener.incoming(&self.address) {
             Err(e) => {
                 let http_error = errors::Error::InitFailure(e.to_string());
                 Err(merrors::ServiceError::from_error(
@@ -177,20 +265,10 @@ impl plugin::service::Service for Http {
                     http_error.into(),
                 ))
             }
-            Ok(incoming) => {
-                if let Err(e) = axum::serve(incoming, self.router(ctx.clone()))
-                    .with_graceful_shutdown(shutdown_signal)
-                    .await
-                {
-                    let http_error = errors::Error::ShutdownFailure(e.to_string());
-                    return Err(merrors::ServiceError::from_error(
-                        ctx.clone(),
-                        http_error.into(),
-                    ));
-                }
-
-                Ok(())
-            }
+            Ok(Incoming::Tcp(incoming)) => self.serve(ctx, incoming, shutdown_signal).await,
+
+            #[cfg(unix)]
+            Ok(Incoming::Unix(incoming)) => self.serve(ctx, incoming, shutdown_signal).await,
         }
     }
 
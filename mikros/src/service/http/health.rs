@@ -1,11 +1,96 @@
+use std::future::Future;
 use std::sync::Arc;
 
+use axum::Json;
+use axum::Router;
 use axum::extract::State;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use http::StatusCode;
+use tokio::sync::watch::Receiver;
 
 use crate::Mutex;
+use crate::definition::service::Address;
+use crate::health::Report;
 use crate::http::ServiceState;
+use crate::service::context::Context;
+use crate::service::http::errors;
+use crate::service::listener::{Incoming, Listener};
 
 // The default /health handler for every application.
 pub(crate) async fn handler(State(_): State<Arc<Mutex<ServiceState>>>) -> String {
     String::new()
 }
+
+// The /health/live handler, reflecting the service liveness probe.
+pub(crate) async fn liveness(State(state): State<Arc<Mutex<ServiceState>>>) -> Response {
+    let ctx = state.lock().await.context();
+    report_response(ctx.liveness())
+}
+
+// The /health/ready handler, reflecting the service readiness probe.
+pub(crate) async fn readiness(State(state): State<Arc<Mutex<ServiceState>>>) -> Response {
+    let ctx = state.lock().await.context();
+    report_response(ctx.readiness().await)
+}
+
+fn report_response(report: Report) -> Response {
+    let code = if report.is_up() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (code, Json(report)).into_response()
+}
+
+// Builds a router with all health endpoints.
+pub(crate) fn router() -> Router<Arc<Mutex<ServiceState>>> {
+    Router::new()
+        .route("/health", get(handler))
+        .route("/health/live", get(liveness))
+        .route("/health/ready", get(readiness))
+}
+
+// Binds a standalone HTTP server, only with the health endpoints, so services
+// that are not HTTP can also expose their probes. The returned future serves
+// requests until the shutdown signal is received.
+pub(crate) async fn bind(
+    ctx: Arc<Context>,
+    address: &Address,
+    shutdown_rx: Receiver<()>,
+) -> Result<impl Future<Output = ()>, errors::Error> {
+    let incoming = Listener::bind(address)
+        .and_then(Listener::into_incoming)
+        .map_err(|e| errors::Error::InitFailure(e.to_string()))?;
+
+    let router = router().with_state(Arc::new(Mutex::new(ServiceState::new(ctx.clone()))));
+    let shutdown_signal = async move {
+        let mut shutdown_rx = shutdown_rx.clone();
+
+        // Wait until the receiver sees the shutdown signal
+        shutdown_rx.changed().await.ok();
+    };
+
+    Ok(async move {
+        let result = match incoming {
+            Incoming::Tcp(listener) => {
+                axum::serve(listener, router)
+                    .with_graceful_shutdown(shutdown_signal)
+                    .await
+            }
+
+            #[cfg(unix)]
+            Incoming::Unix(listener) => {
+                axum::serve(listener, router)
+                    .with_graceful_shutdown(shutdown_signal)
+                    .await
+            }
+        };
+
+        if let Err(e) = result {
+            let http_error = errors::Error::ShutdownFailure(e.to_string());
+            ctx.logger_ref().error(&http_error.description());
+        }
+    })
+}
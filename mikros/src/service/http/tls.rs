@@ -0,0 +1,306 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::Request;
+use axum::extract::connect_info::{ConnectInfo, Connected};
+use axum::middleware::Next;
+use axum::response::Response;
+use axum::serve::{IncomingStream, Listener};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::server::TlsStream;
+
+use crate::tls::PeerIdentity;
+
+// How long a client has to complete its TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Information about an established TLS connection.
+#[derive(Clone, Debug)]
+pub(crate) struct TlsConnectInfo<A> {
+    pub(crate) addr: A,
+    pub(crate) peer: Option<PeerIdentity>,
+}
+
+impl<L> Connected<IncomingStream<'_, TlsListener<L>>> for TlsConnectInfo<L::Addr>
+where
+    L: Listener,
+    L::Addr: Clone + Send + Sync + 'static,
+{
+    fn connect_info(stream: IncomingStream<'_, TlsListener<L>>) -> Self {
+        stream.remote_addr().clone()
+    }
+}
+
+type Connection<L> = (
+    TlsStream<<L as Listener>::Io>,
+    TlsConnectInfo<<L as Listener>::Addr>,
+);
+
+// A listener that terminates TLS for every connection accepted by an inner
+// listener. Each handshake runs in its own task, so slow clients can't hold
+// new connections.
+pub(crate) struct TlsListener<L: Listener> {
+    addr: L::Addr,
+    connections: mpsc::Receiver<Connection<L>>,
+    acceptor: JoinHandle<()>,
+}
+
+impl<L> TlsListener<L>
+where
+    L: Listener,
+    L::Addr: Clone + Send + Sync + 'static,
+{
+    pub(crate) fn new(listener: L, config: Arc<ServerConfig>) -> io::Result<Self> {
+        let addr = listener.local_addr()?;
+        let (tx, rx) = mpsc::channel(64);
+        let acceptor = tokio::spawn(Self::accept_connections(
+            listener,
+            TlsAcceptor::from(config),
+            tx,
+        ));
+
+        Ok(Self {
+            addr,
+            connections: rx,
+            acceptor,
+        })
+    }
+
+    async fn accept_connections(
+        mut listener: L,
+        acceptor: TlsAcceptor,
+        tx: mpsc::Sender<Connection<L>>,
+    ) {
+        loop {
+            // The inner listener already handles (and retries) accept errors.
+            let (stream, addr) = tokio::select! {
+                _ = tx.closed() => return,
+                accepted = listener.accept() => accepted,
+            };
+
+            let acceptor = acceptor.clone();
+            let tx = tx.clone();
+
+            tokio::spawn(async move {
+                // Connections failing the handshake are just dropped.
+                if let Ok(Ok(stream)) =
+                    tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                {
+                    let peer = stream
+                        .get_ref()
+                        .1
+                        .peer_certificates()
+                        .and_then(|certs| PeerIdentity::new(certs.to_vec()));
+
+                    tx.send((stream, TlsConnectInfo { addr, peer })).await.ok();
+                }
+            });
+        }
+    }
+}
+
+impl<L: Listener> Drop for TlsListener<L> {
+    fn drop(&mut self) {
+        self.acceptor.abort();
+    }
+}
+
+impl<L> Listener for TlsListener<L>
+where
+    L: Listener,
+    L::Addr: Clone + Send + Sync + 'static,
+{
+    type Io = TlsStream<L::Io>;
+    type Addr = TlsConnectInfo<L::Addr>;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+
+            // The acceptor task only finishes when the listener is dropped.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(TlsConnectInfo {
+            addr: self.addr.clone(),
+            peer: None,
+        })
+    }
+}
+
+// Middleware that exposes the client identity through the request extensions.
+// The client address is also added, so handlers can keep using the standard
+// ConnectInfo extractor.
+pub(crate) async fn peer_identity<A>(
+    ConnectInfo(info): ConnectInfo<TlsConnectInfo<A>>,
+    mut req: Request,
+    next: Next,
+) -> Response
+where
+    A: Clone + Send + Sync + 'static,
+{
+    req.extensions_mut().insert(ConnectInfo(info.addr));
+
+    if let Some(peer) = info.peer {
+        req.extensions_mut().insert(peer);
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use axum::Router;
+    use axum::extract::Extension;
+    use axum::routing::get;
+    use std::net::SocketAddr;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio_rustls::TlsConnector;
+    use tokio_rustls::rustls::pki_types::ServerName;
+    use tokio_rustls::rustls::{ClientConfig, RootCertStore, crypto};
+
+    use super::*;
+    use crate::tls::{Config, Settings};
+    use mikros_tests::common::assets_path;
+
+    fn pem_reader(name: &str) -> BufReader<File> {
+        BufReader::new(File::open(assets_path().join("tls").join(name)).unwrap())
+    }
+
+    fn client_config() -> Arc<ClientConfig> {
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut pem_reader("ca.pem")) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+
+        let certs = rustls_pemfile::certs(&mut pem_reader("client.pem"))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = rustls_pemfile::private_key(&mut pem_reader("client.key"))
+            .unwrap()
+            .unwrap();
+
+        let config =
+            ClientConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots)
+                .with_client_auth_cert(certs, key)
+                .unwrap();
+
+        Arc::new(config)
+    }
+
+    fn server_config() -> Arc<ServerConfig> {
+        let settings: Settings = serde_json::from_value(serde_json::json!({
+            "cert": assets_path().join("tls/server.pem"),
+            "key": assets_path().join("tls/server.key"),
+            "client_ca": assets_path().join("tls/ca.pem"),
+        }))
+        .unwrap();
+
+        let envs = crate::env::Env::load(
+            &crate::definition::Definitions::new(
+                assets_path().join("definitions/service.toml.ok").to_str(),
+                None,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        Config::load(
+            &crate::definition::ServiceKind::Script,
+            Some(settings),
+            &envs,
+        )
+        .unwrap()
+        .unwrap()
+        .server_config()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mutual_tls_peer_identity() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let listener = TlsListener::new(listener, server_config()).unwrap();
+
+        let router = Router::new()
+            .route(
+                "/peer",
+                get(|Extension(peer): Extension<PeerIdentity>| async move {
+                    format!("chain={}", peer.chain().len())
+                }),
+            )
+            .layer(axum::middleware::from_fn(peer_identity::<SocketAddr>));
+
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                router.into_make_service_with_connect_info::<TlsConnectInfo<SocketAddr>>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut stream = TlsConnector::from(client_config())
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+            .unwrap();
+
+        stream
+            .write_all(b"GET /peer HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.ok();
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("chain=1"));
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_required() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut listener = TlsListener::new(listener, server_config()).unwrap();
+
+        // A client without a certificate must not get a connection through.
+        let mut roots = RootCertStore::empty();
+        for cert in rustls_pemfile::certs(&mut pem_reader("ca.pem")) {
+            roots.add(cert.unwrap()).unwrap();
+        }
+
+        let config =
+            ClientConfig::builder_with_provider(Arc::new(crypto::ring::default_provider()))
+                .with_safe_default_protocol_versions()
+                .unwrap()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        if let Ok(mut stream) = TlsConnector::from(Arc::new(config))
+            .connect(ServerName::try_from("localhost").unwrap(), stream)
+            .await
+        {
+            // With TLS 1.3 the client only sees the failure when reading.
+            let mut buf = Vec::new();
+            assert!(stream.read_to_end(&mut buf).await.is_err() || buf.is_empty());
+        }
+
+        let accepted = tokio::time::timeout(Duration::from_millis(200), listener.accept()).await;
+        assert!(accepted.is_err());
+    }
+}
@@ -0,0 +1,292 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use tokio::net::TcpListener;
+#[cfg(unix)]
+use tokio::net::UnixListener;
+
+use crate::definition::service::Address;
+use crate::definition::{Definitions, ServiceKind};
+use crate::env::Env;
+use crate::env_is_default;
+use crate::errors as merrors;
+use crate::service::context::Context;
+use crate::service::errors;
+
+// Resolves the address where a service kind must listen for connections. An
+// address set through the environment takes precedence over the service.toml
+// one, while the port variable keeps overriding only the port.
+pub(crate) fn resolve_address(
+    kind: ServiceKind,
+    definitions: &Definitions,
+    envs: &Env,
+) -> Result<Address, merrors::Error> {
+    let (env_address, env_port, default_port) = match kind {
+        ServiceKind::Http => (
+            &envs.http_address,
+            envs.http_port,
+            env_is_default!(envs, http_port),
+        ),
+        ServiceKind::Grpc => (
+            &envs.grpc_address,
+            envs.grpc_port,
+            env_is_default!(envs, grpc_port),
+        ),
+        _ => return Err(errors::Error::UnsupportedAddress(kind.to_string()).into()),
+    };
+
+    if let Some(address) = env_address {
+        return address
+            .parse::<Address>()
+            .map_err(|e| errors::Error::InvalidAddress(kind.to_string(), e).into());
+    }
+
+    let env_port = valid_port(&kind.to_string(), env_port)?;
+    let service_type = definitions.get_service_type(kind)?;
+    Ok(match &service_type.1 {
+        None => Address::Port(env_port),
+        Some(Address::Port(port)) => Address::Port(if default_port { *port } else { env_port }),
+        Some(Address::Socket(addr)) => {
+            let mut addr = *addr;
+            if !default_port {
+                addr.set_port(env_port);
+            }
+
+            Address::Socket(addr)
+        }
+        Some(address) => address.clone(),
+    })
+}
+
+// Resolves the address of the standalone server answering the health probes,
+// if one was set. As with service kinds, a complete address takes precedence
+// over the port.
+pub(crate) fn resolve_health_address(envs: &Env) -> Result<Option<Address>, merrors::Error> {
+    if let Some(address) = &envs.health_address {
+        return address
+            .parse::<Address>()
+            .map(Some)
+            .map_err(|e| errors::Error::InvalidAddress("health".to_string(), e).into());
+    }
+
+    envs.health_port
+        .map(|port| valid_port("health", port).map(Address::Port))
+        .transpose()
+}
+
+// Checks if a port read from the environment is a valid TCP port.
+fn valid_port(kind: &str, port: i32) -> Result<u16, merrors::Error> {
+    u16::try_from(port).map_err(|_| {
+        errors::Error::InvalidAddress(kind.to_string(), format!("invalid port {port}")).into()
+    })
+}
+
+// A listener bound to one of the supported address kinds. Services bind it
+// while initializing, so the real address (even when an ephemeral port is
+// used) is already known before they start running.
+pub(crate) enum Listener {
+    Tcp(std::net::TcpListener),
+
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixListener),
+}
+
+// The async version of a Listener, used to serve connections.
+pub(crate) enum Incoming {
+    Tcp(TcpListener),
+
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    pub(crate) fn bind(address: &Address) -> io::Result<Self> {
+        let listener = match address {
+            Address::Port(port) => {
+                let addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), *port);
+                Listener::Tcp(std::net::TcpListener::bind(addr)?)
+            }
+            Address::Socket(addr) => Listener::Tcp(std::net::TcpListener::bind(addr)?),
+
+            #[cfg(unix)]
+            Address::Unix(path) => {
+                remove_stale_socket(path)?;
+                Listener::Unix(std::os::unix::net::UnixListener::bind(path)?)
+            }
+
+            #[cfg(not(unix))]
+            Address::Unix(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unix sockets are not supported on this platform",
+                ));
+            }
+        };
+
+        listener.set_nonblocking()?;
+        Ok(listener)
+    }
+
+    fn set_nonblocking(&self) -> io::Result<()> {
+        match self {
+            Listener::Tcp(listener) => listener.set_nonblocking(true),
+
+            #[cfg(unix)]
+            Listener::Unix(listener) => listener.set_nonblocking(true),
+        }
+    }
+
+    // Returns the address the listener is really bound to.
+    pub(crate) fn local_address(&self) -> io::Result<Address> {
+        match self {
+            Listener::Tcp(listener) => Ok(Address::Socket(listener.local_addr()?)),
+
+            #[cfg(unix)]
+            Listener::Unix(listener) => match listener.local_addr()?.as_pathname() {
+                Some(path) => Ok(Address::Unix(path.to_path_buf())),
+                None => Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "unix socket without a path",
+                )),
+            },
+        }
+    }
+
+    // Converts the listener into its async version. It must be called from
+    // inside the tokio runtime.
+    pub(crate) fn into_incoming(self) -> io::Result<Incoming> {
+        match self {
+            Listener::Tcp(listener) => Ok(Incoming::Tcp(TcpListener::from_std(listener)?)),
+
+            #[cfg(unix)]
+            Listener::Unix(listener) => Ok(Incoming::Unix(UnixListener::from_std(listener)?)),
+        }
+    }
+}
+
+// A listener bound while the service initializes. It is shared between the
+// service clones, since the one that runs is not the one initialized.
+#[derive(Clone, Default)]
+pub(crate) struct BoundListener {
+    inner: Arc<Mutex<Option<Listener>>>,
+}
+
+impl BoundListener {
+    // Binds the address of a service kind and registers the real bound
+    // address inside the context, returning it.
+    pub(crate) fn bind(
+        &self,
+        ctx: &Context,
+        kind: &ServiceKind,
+        address: &Address,
+    ) -> Result<Address, merrors::Error> {
+        let bind_error =
+            |e: io::Error| errors::Error::BindFailure(address.to_string(), e.to_string());
+        let listener = Listener::bind(address).map_err(bind_error)?;
+        let local_address = listener.local_address().map_err(bind_error)?;
+
+        *self.inner.lock().unwrap() = Some(listener);
+        ctx.set_bound_address(kind, local_address.clone());
+
+        Ok(local_address)
+    }
+
+    // Gives the bound listener, ready to serve connections. If it was already
+    // used, the address is bound again.
+    pub(crate) fn incoming(&self, address: &Address) -> io::Result<Incoming> {
+        let listener = self.inner.lock().unwrap().take();
+
+        match listener {
+            Some(listener) => listener.into_incoming(),
+            None => Listener::bind(address)?.into_incoming(),
+        }
+    }
+}
+
+// Removes a socket file left behind by a previous execution, since binding
+// to an existing path always fails. Other kinds of files are kept.
+#[cfg(unix)]
+fn remove_stale_socket(path: &std::path::Path) -> io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mikros_tests::common::assets_path;
+
+    fn load(name: &str) -> (Arc<Definitions>, Arc<Env>) {
+        let filename = assets_path().join("definitions").join(name);
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        let envs = Env::load(&defs).unwrap();
+
+        (defs, envs)
+    }
+
+    #[test]
+    fn test_resolve_address() {
+        let (defs, envs) = load("service.toml.ok");
+        assert_eq!(
+            resolve_address(ServiceKind::Grpc, &defs, &envs).unwrap(),
+            Address::Port(7070)
+        );
+        assert!(resolve_address(ServiceKind::Script, &defs, &envs).is_err());
+
+        let (defs, envs) = load("service.toml.ok_address");
+        assert_eq!(
+            resolve_address(ServiceKind::Grpc, &defs, &envs).unwrap(),
+            Address::Socket("[::1]:7071".parse().unwrap())
+        );
+        assert_eq!(
+            resolve_address(ServiceKind::Http, &defs, &envs).unwrap(),
+            Address::Unix("/tmp/my-hybrid-service.sock".into())
+        );
+    }
+
+    #[test]
+    fn test_resolve_address_invalid_port() {
+        let filename = assets_path().join("definitions").join("service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+
+        for port in ["70000", "-1"] {
+            let source = |name: &str| match name {
+                "MIKROS_GRPC_PORT" | "MIKROS_HEALTH_PORT" => Some(port.to_string()),
+                _ => None,
+            };
+
+            let envs = Env::load_from_source(&defs, &source).unwrap();
+            assert!(resolve_address(ServiceKind::Grpc, &defs, &envs).is_err());
+            assert!(resolve_health_address(&envs).is_err());
+        }
+    }
+
+    #[test]
+    fn test_bind_ephemeral_port() {
+        let address = Address::Socket("127.0.0.1:0".parse().unwrap());
+        let listener = Listener::bind(&address).unwrap();
+        let address = listener.local_address().unwrap();
+
+        assert!(matches!(address, Address::Socket(_)));
+        assert_ne!(address.port(), Some(0));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bind_unix_socket() {
+        let path = std::env::temp_dir().join(format!("mikros-{}.sock", std::process::id()));
+        let address = Address::Unix(path.clone());
+
+        let listener = Listener::bind(&address).unwrap();
+        assert_eq!(listener.local_address().unwrap(), address);
+
+        // A second bind must replace the stale socket file.
+        assert!(Listener::bind(&address).is_ok());
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use tokio::sync::watch;
+use tokio::time;
+
+use crate::definition::{CustomServiceInfo, Definitions};
+use crate::errors as merrors;
+use crate::service::builder::DefinitionsCheck;
+use crate::service::context::Context;
+
+// Watcher polls the service definitions files and, when some of them change,
+// reloads and validates their content before applying it to the running
+// service.
+pub(crate) struct Watcher {
+    paths: Vec<String>,
+    overrides: Vec<(String, String)>,
+    interval: Duration,
+    envs: HashMap<String, String>,
+    custom_types: Vec<String>,
+    check: Option<DefinitionsCheck>,
+}
+
+impl Watcher {
+    pub(crate) fn new(
+        paths: Vec<String>,
+        overrides: Vec<(String, String)>,
+        interval: Duration,
+        envs: HashMap<String, String>,
+        custom_types: Vec<String>,
+        check: Option<DefinitionsCheck>,
+    ) -> Self {
+        Self {
+            paths,
+            overrides,
+            interval,
+            envs,
+            custom_types,
+            check,
+        }
+    }
+
+    pub(crate) async fn run(self, ctx: Arc<Context>, mut shutdown_rx: watch::Receiver<()>) {
+        let mut modified = self.modified_times();
+        let mut interval = time::interval(self.interval);
+
+        // The first tick completes immediately.
+        interval.tick().await;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_rx.changed() => return,
+                _ = interval.tick() => {}
+            }
+
+            let current = self.modified_times();
+            if current == modified {
+                continue;
+            }
+
+            modified = current;
+            let definitions = match self.load(&ctx) {
+                Ok(definitions) => definitions,
+                Err(e) => {
+                    Self::reject(&ctx, &e.description());
+                    continue;
+                }
+            };
+
+            if let Err(e) = ctx
+                .check_definitions(&definitions, self.check.as_ref())
+                .await
+            {
+                Self::reject(&ctx, &e.to_string());
+                continue;
+            }
+
+            ctx.apply_definitions(Arc::new(definitions)).await;
+            ctx.logger_ref().info("service definitions reloaded");
+        }
+    }
+
+    fn reject(ctx: &Context, error: &str) {
+        ctx.logger_ref().errorf(
+            "service definitions reload rejected",
+            serde_json::json!({ "error": error }),
+        );
+    }
+
+    fn modified_times(&self) -> Vec<Option<SystemTime>> {
+        self.files()
+            .iter()
+            .map(|p| std::fs::metadata(p).and_then(|m| m.modified()).ok())
+            .collect()
+    }
+
+    fn files(&self) -> Vec<String> {
+        if self.paths.is_empty() {
+            return vec!["service.toml".to_string()];
+        }
+
+        self.paths.clone()
+    }
+
+    // Loads the definitions the same way they are loaded when the service
+    // starts.
+    fn load(&self, ctx: &Context) -> Result<Definitions, merrors::Error> {
+        let source = |name: &str| {
+            self.envs
+                .get(name)
+                .cloned()
+                .or_else(|| std::env::var(name).ok())
+        };
+
+        let definitions = Definitions::load(&self.files())?
+            .with_deployment(&ctx.env_ref().deployment_env)?
+            .with_overrides(&self.overrides)?
+            .interpolate(&source)?;
+
+        definitions.validate(Some(CustomServiceInfo {
+            types: Some(self.custom_types.clone()),
+        }))?;
+
+        Ok(definitions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::Env;
+    use crate::logger::builder::LoggerBuilder;
+
+    const DEFINITIONS: &str = r#"
+        name = "my-service"
+        types = ["grpc"]
+        version = "v1.0.0"
+        language = "rust"
+        product = "incredible-product"
+
+        [log]
+        level = "LEVEL"
+        "#;
+
+    #[tokio::test]
+    async fn test_reload_definitions() {
+        let path = std::env::temp_dir().join(format!("mikros-reload-{}.toml", std::process::id()));
+        std::fs::write(&path, DEFINITIONS.replace("LEVEL", "info")).unwrap();
+
+        let paths = vec![path.to_str().unwrap().to_string()];
+        let defs = Definitions::load(&paths).unwrap();
+        let env = Env::load(&defs).unwrap();
+        let logger = Arc::new(LoggerBuilder::new().build());
+        let ctx = Arc::new(Context::new(env, logger, Arc::new(defs), vec![]));
+        let mut definitions_rx = ctx.subscribe_definitions();
+
+        let (shutdown_tx, shutdown_rx) = watch::channel(());
+        let watcher = Watcher::new(
+            paths,
+            vec![],
+            Duration::from_millis(10),
+            HashMap::new(),
+            vec![],
+            None,
+        );
+        let task = tokio::spawn(watcher.run(ctx.clone(), shutdown_rx));
+
+        // Invalid content is rejected.
+        time::sleep(Duration::from_millis(50)).await;
+        std::fs::write(&path, DEFINITIONS.replace("\"LEVEL\"", "[")).unwrap();
+        time::sleep(Duration::from_millis(50)).await;
+        assert!(!definitions_rx.has_changed().unwrap());
+
+        std::fs::write(&path, DEFINITIONS.replace("LEVEL", "debug")).unwrap();
+        time::timeout(Duration::from_secs(5), definitions_rx.changed())
+            .await
+            .unwrap()
+            .unwrap();
+
+        let definitions = definitions_rx.borrow_and_update().clone();
+        assert_eq!(definitions.log().level, Some("debug".to_string()));
+        assert_eq!(ctx.definitions().log().level, Some("info".to_string()));
+
+        shutdown_tx.send(()).unwrap();
+        task.await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[derive(Clone, Default)]
+    struct ReloadFeature {
+        settings: Option<serde_json::Value>,
+    }
+
+    #[async_trait::async_trait]
+    impl crate::plugin::feature::Feature for ReloadFeature {
+        fn name(&self) -> &str {
+            "reload_feature"
+        }
+
+        fn info(&self) -> Option<serde_json::Value> {
+            None
+        }
+
+        fn is_enabled(&self) -> bool {
+            true
+        }
+
+        fn can_be_initialized(&self, _: Arc<Definitions>, _: Arc<Env>) -> merrors::Result<bool> {
+            Ok(true)
+        }
+
+        async fn initialize(&mut self, _: Arc<Context>) -> merrors::Result<()> {
+            Ok(())
+        }
+
+        async fn cleanup(&self) {}
+
+        async fn on_definitions_changed(
+            &mut self,
+            ctx: Arc<Context>,
+            definitions: Arc<Definitions>,
+        ) -> merrors::Result<()> {
+            ctx.feature(self.name()).await?;
+            self.settings = definitions.feature(self.name());
+            Ok(())
+        }
+
+        fn service_api(&self) -> Option<&dyn std::any::Any> {
+            Some(self)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_apply_definitions_with_hook_using_context() {
+        let content = DEFINITIONS.replace("LEVEL", "info");
+        let defs = Definitions::from_toml(&content).unwrap();
+        let env = Env::load(&defs).unwrap();
+        let logger = Arc::new(LoggerBuilder::new().build());
+        let ctx = Arc::new(Context::new(
+            env,
+            logger,
+            Arc::new(defs),
+            vec![Box::new(ReloadFeature::default())],
+        ));
+
+        let definitions = Definitions::from_toml(&format!(
+            "{content}\n[features.reload_feature]\nenabled = true\n"
+        ))
+        .unwrap();
+
+        time::timeout(
+            Duration::from_secs(5),
+            ctx.apply_definitions(Arc::new(definitions)),
+        )
+        .await
+        .expect("applying definitions deadlocked");
+
+        let feature = ctx.feature("reload_feature").await.unwrap();
+        let feature = feature
+            .service_api()
+            .and_then(|api| api.downcast_ref::<ReloadFeature>())
+            .unwrap();
+
+        assert_eq!(
+            feature.settings,
+            Some(serde_json::json!({ "enabled": true }))
+        );
+    }
+}
@@ -0,0 +1,297 @@
+mod errors;
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_derive::Deserialize;
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::WebPkiClientVerifier;
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+use crate::definition::ServiceKind;
+use crate::env::Env;
+
+/// The identity of a client that presented a certificate while establishing
+/// a TLS connection with the service. When TLS is enabled, it is available
+/// inside the request extensions of both HTTP and gRPC handlers.
+///
+/// ```
+/// use mikros::tls::PeerIdentity;
+///
+/// fn client_certificate(req: &mikros::tonic::Request<()>) -> Option<Vec<u8>> {
+///     req.extensions()
+///         .get::<PeerIdentity>()
+///         .map(|peer| peer.certificate().to_vec())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PeerIdentity {
+    certificates: Arc<Vec<CertificateDer<'static>>>,
+}
+
+impl PeerIdentity {
+    pub(crate) fn new(certificates: Vec<CertificateDer<'static>>) -> Option<Self> {
+        if certificates.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            certificates: Arc::new(certificates),
+        })
+    }
+
+    /// Returns the DER encoded certificate of the client.
+    pub fn certificate(&self) -> &[u8] {
+        self.certificates[0].as_ref()
+    }
+
+    /// Returns the DER encoded certificate chain presented by the client,
+    /// starting with its own certificate.
+    pub fn chain(&self) -> Vec<&[u8]> {
+        self.certificates.iter().map(|c| c.as_ref()).collect()
+    }
+}
+
+// TLS settings of a service kind, loaded from the 'tls' entry of its section
+// inside the service.toml file.
+#[derive(Deserialize, Clone, Debug, Default)]
+pub(crate) struct Settings {
+    cert: Option<String>,
+    key: Option<String>,
+    client_ca: Option<String>,
+    require_client_cert: Option<bool>,
+}
+
+// The TLS configuration of a server.
+#[derive(Clone, Debug)]
+pub(crate) struct Config {
+    cert: PathBuf,
+    key: PathBuf,
+    client_ca: Option<PathBuf>,
+    require_client_cert: bool,
+}
+
+impl Config {
+    // Loads the TLS configuration of a service kind, where files set through
+    // environment variables take precedence over the service.toml ones.
+    // Returns None when TLS is not enabled for the service.
+    pub(crate) fn load(
+        kind: &ServiceKind,
+        settings: Option<Settings>,
+        envs: &Env,
+    ) -> Result<Option<Self>, errors::Error> {
+        let settings = settings.unwrap_or_default();
+        let (cert, key, client_ca) = match kind {
+            ServiceKind::Http => (
+                envs.http_tls_cert.clone().or(settings.cert),
+                envs.http_tls_key.clone().or(settings.key),
+                envs.http_tls_client_ca.clone().or(settings.client_ca),
+            ),
+            ServiceKind::Grpc => (
+                envs.grpc_tls_cert.clone().or(settings.cert),
+                envs.grpc_tls_key.clone().or(settings.key),
+                envs.grpc_tls_client_ca.clone().or(settings.client_ca),
+            ),
+            _ => (settings.cert, settings.key, settings.client_ca),
+        };
+
+        let (cert, key) = match (cert, key) {
+            (None, None) if client_ca.is_none() => return Ok(None),
+            (Some(cert), Some(key)) => (cert, key),
+            _ => return Err(errors::Error::IncompleteSettings(kind.to_string())),
+        };
+
+        // Client certificates are required by default once a CA is set.
+        let require_client_cert = settings.require_client_cert.unwrap_or(client_ca.is_some());
+        if require_client_cert && client_ca.is_none() {
+            return Err(errors::Error::MissingClientCa(kind.to_string()));
+        }
+
+        Ok(Some(Self {
+            cert: PathBuf::from(cert),
+            key: PathBuf::from(key),
+            client_ca: client_ca.map(PathBuf::from),
+            require_client_cert,
+        }))
+    }
+
+    pub(crate) fn client_auth(&self) -> &'static str {
+        match (&self.client_ca, self.require_client_cert) {
+            (None, _) => "none",
+            (Some(_), false) => "optional",
+            (Some(_), true) => "required",
+        }
+    }
+
+    // Builds the rustls server configuration, used to terminate TLS for
+    // HTTP connections.
+    pub(crate) fn server_config(&self) -> Result<Arc<rustls::ServerConfig>, errors::Error> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = rustls::ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .map_err(|e| errors::Error::InvalidConfig(e.to_string()))?;
+
+        let builder = match &self.client_ca {
+            None => builder.with_no_client_auth(),
+            Some(client_ca) => {
+                let mut roots = rustls::RootCertStore::empty();
+                for cert in Self::load_certs(client_ca)? {
+                    roots
+                        .add(cert)
+                        .map_err(|e| errors::Error::InvalidConfig(e.to_string()))?;
+                }
+
+                let mut verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+
+                if !self.require_client_cert {
+                    verifier = verifier.allow_unauthenticated();
+                }
+
+                builder.with_client_cert_verifier(
+                    verifier
+                        .build()
+                        .map_err(|e| errors::Error::InvalidConfig(e.to_string()))?,
+                )
+            }
+        };
+
+        let mut config = builder
+            .with_single_cert(Self::load_certs(&self.cert)?, Self::load_key(&self.key)?)
+            .map_err(|e| errors::Error::InvalidConfig(e.to_string()))?;
+
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+        Ok(Arc::new(config))
+    }
+
+    // Builds the tonic server TLS configuration.
+    pub(crate) fn grpc_config(&self) -> Result<ServerTlsConfig, errors::Error> {
+        // tonic only parses the files when the server starts, so we validate
+        // them here to report errors while the service is initializing.
+        self.server_config()?;
+
+        let identity = Identity::from_pem(Self::read(&self.cert)?, Self::read(&self.key)?);
+        let mut config = ServerTlsConfig::new().identity(identity);
+
+        if let Some(client_ca) = &self.client_ca {
+            config = config
+                .client_ca_root(Certificate::from_pem(Self::read(client_ca)?))
+                .client_auth_optional(!self.require_client_cert);
+        }
+
+        Ok(config)
+    }
+
+    fn read(path: &Path) -> Result<Vec<u8>, errors::Error> {
+        std::fs::read(path).map_err(|e| Self::file_error(path, e))
+    }
+
+    fn open(path: &Path) -> Result<BufReader<File>, errors::Error> {
+        File::open(path)
+            .map(BufReader::new)
+            .map_err(|e| Self::file_error(path, e))
+    }
+
+    fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, errors::Error> {
+        let certs = rustls_pemfile::certs(&mut Self::open(path)?)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Self::file_error(path, e))?;
+
+        if certs.is_empty() {
+            return Err(Self::file_error(path, "no certificate found"));
+        }
+
+        Ok(certs)
+    }
+
+    fn load_key(path: &Path) -> Result<PrivateKeyDer<'static>, errors::Error> {
+        rustls_pemfile::private_key(&mut Self::open(path)?)
+            .map_err(|e| Self::file_error(path, e))?
+            .ok_or_else(|| Self::file_error(path, "no private key found"))
+    }
+
+    fn file_error(path: &Path, e: impl ToString) -> errors::Error {
+        errors::Error::CouldNotLoadFile(path.display().to_string(), e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::definition::Definitions;
+    use mikros_tests::common::assets_path;
+
+    fn load_env() -> Arc<Env> {
+        let filename = assets_path().join("definitions/service.toml.ok");
+        let defs = Definitions::new(filename.to_str(), None).unwrap();
+        Env::load(&defs).unwrap()
+    }
+
+    fn settings(client_ca: bool, require_client_cert: Option<bool>) -> Settings {
+        let path = |name: &str| assets_path().join("tls").join(name).display().to_string();
+
+        Settings {
+            cert: Some(path("server.pem")),
+            key: Some(path("server.key")),
+            client_ca: client_ca.then(|| path("ca.pem")),
+            require_client_cert,
+        }
+    }
+
+    #[test]
+    fn test_tls_disabled() {
+        let envs = load_env();
+        let config = Config::load(&ServiceKind::Script, None, &envs);
+        assert!(matches!(config, Ok(None)));
+    }
+
+    #[test]
+    fn test_tls_config() {
+        let envs = load_env();
+
+        let config = Config::load(&ServiceKind::Script, Some(settings(false, None)), &envs)
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.client_auth(), "none");
+        assert!(config.server_config().is_ok());
+        assert!(config.grpc_config().is_ok());
+
+        let config = Config::load(&ServiceKind::Script, Some(settings(true, None)), &envs)
+            .unwrap()
+            .unwrap();
+        assert_eq!(config.client_auth(), "required");
+        assert!(config.server_config().is_ok());
+
+        let config = Config::load(
+            &ServiceKind::Script,
+            Some(settings(true, Some(false))),
+            &envs,
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(config.client_auth(), "optional");
+        assert!(config.server_config().is_ok());
+    }
+
+    #[test]
+    fn test_invalid_tls_config() {
+        let envs = load_env();
+
+        let mut incomplete = settings(false, None);
+        incomplete.key = None;
+        assert!(Config::load(&ServiceKind::Script, Some(incomplete), &envs).is_err());
+
+        let without_ca = settings(false, Some(true));
+        assert!(Config::load(&ServiceKind::Script, Some(without_ca), &envs).is_err());
+
+        let mut invalid_key = settings(false, None);
+        invalid_key.key = invalid_key.cert.clone();
+        let config = Config::load(&ServiceKind::Script, Some(invalid_key), &envs)
+            .unwrap()
+            .unwrap();
+        assert!(config.server_config().is_err());
+    }
+}
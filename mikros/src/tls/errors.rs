@@ -0,0 +1,9 @@
+// Module internal errors
+crate::module_errors!(
+    Error {
+        IncompleteSettings(s: String) => "incomplete TLS settings for '{}' service: both cert and key must be set",
+        MissingClientCa(s: String) => "'{}' service requires client certificates but no client_ca was set",
+        CouldNotLoadFile(f: String, e: String) => "could not load TLS file '{}': {}",
+        InvalidConfig(e: String) => "invalid TLS configuration: {}"
+    }
+);